@@ -0,0 +1,72 @@
+use {
+    super::{
+        mouse_position::{self, MousePosition},
+        player::{Player, PlayerAction},
+        tile::AVG_TILE_DIMENSION,
+    },
+    bevy::{input::mouse::MouseMotion, prelude::*},
+    leafwing_input_manager::prelude::*,
+};
+
+const STICK_AIM_DEADZONE: f32 = 0.3;
+const STICK_AIM_DISTANCE: f32 = AVG_TILE_DIMENSION;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum AimDevice {
+    Mouse,
+    Gamepad,
+}
+
+//The world position the player is aiming at, following whichever of the mouse or right stick was
+//used last. Stick aim is placed just in front of the player so it can also pick out nearby tasks
+#[derive(Resource)]
+pub struct AimPosition {
+    device: AimDevice,
+    stick_dir: Vec2,
+    pos: Vec2,
+}
+
+impl AimPosition {
+    pub fn as_vec(&self) -> Vec2 {
+        self.pos
+    }
+}
+
+fn update_aim_position(
+    mut aim_pos: ResMut<AimPosition>,
+    mouse_pos: Res<MousePosition>,
+    mut mouse_motion_evr: EventReader<MouseMotion>,
+    mouse_btns: Res<ButtonInput<MouseButton>>,
+    player_qry: Query<(&Transform, &ActionState<PlayerAction>), With<Player>>,
+) {
+    let mouse_used =
+        mouse_motion_evr.read().count() > 0 || mouse_btns.get_just_pressed().next().is_some();
+    let Ok((player_xform, player_in)) = player_qry.get_single() else {
+        return;
+    };
+    let stick = player_in.axis_pair(&PlayerAction::Aim);
+    if stick.length() > STICK_AIM_DEADZONE {
+        aim_pos.device = AimDevice::Gamepad;
+        aim_pos.stick_dir = stick.normalize();
+    } else if mouse_used {
+        aim_pos.device = AimDevice::Mouse;
+    }
+    aim_pos.pos = match aim_pos.device {
+        AimDevice::Mouse => mouse_pos.as_vec(),
+        AimDevice::Gamepad => {
+            player_xform.translation.truncate() + aim_pos.stick_dir * STICK_AIM_DISTANCE
+        }
+    };
+}
+
+pub fn aim_plugin(app: &mut App) {
+    app.insert_resource(AimPosition {
+        device: AimDevice::Mouse,
+        stick_dir: Vec2::X,
+        pos: Vec2::ZERO,
+    })
+    .add_systems(
+        Update,
+        update_aim_position.after(mouse_position::update_mouse_position),
+    );
+}
@@ -0,0 +1,141 @@
+use {super::game_state::GameState, bevy::prelude::*, bevy_rapier2d::prelude::*, std::borrow::Cow};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum AnimationMode {
+    Looping,
+    OneShot,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct AnimationClip {
+    frames: Cow<'static, [usize]>,
+    frame_secs: f32,
+    mode: AnimationMode,
+}
+
+impl AnimationClip {
+    pub fn new(
+        frames: impl Into<Cow<'static, [usize]>>,
+        frame_secs: f32,
+        mode: AnimationMode,
+    ) -> Self {
+        Self {
+            frames: frames.into(),
+            frame_secs,
+            mode,
+        }
+    }
+
+    pub const fn looping(frames: &'static [usize], frame_secs: f32) -> Self {
+        Self {
+            frames: Cow::Borrowed(frames),
+            frame_secs,
+            mode: AnimationMode::Looping,
+        }
+    }
+
+    pub const fn one_shot(frames: &'static [usize], frame_secs: f32) -> Self {
+        Self {
+            frames: Cow::Borrowed(frames),
+            frame_secs,
+            mode: AnimationMode::OneShot,
+        }
+    }
+
+    pub fn first_frame(&self) -> usize {
+        self.frames[0]
+    }
+}
+
+#[derive(Component)]
+pub struct SpriteAnimation {
+    clip: AnimationClip,
+    frame: usize,
+    timer: Timer,
+}
+
+impl SpriteAnimation {
+    pub fn new(clip: AnimationClip) -> Self {
+        Self {
+            timer: Timer::from_seconds(clip.frame_secs, TimerMode::Repeating),
+            clip,
+            frame: 0,
+        }
+    }
+
+    pub fn play(&mut self, clip: AnimationClip) {
+        if self.clip == clip && clip.mode == AnimationMode::Looping {
+            return;
+        }
+        *self = Self::new(clip);
+    }
+
+    pub fn is_playing(&self, clip: &AnimationClip) -> bool {
+        self.clip == *clip
+    }
+
+    pub fn is_playing_one_shot(&self) -> bool {
+        self.clip.mode == AnimationMode::OneShot && self.frame < self.clip.frames.len()
+    }
+
+    fn current_frame(&self) -> usize {
+        self.clip.frames[self.frame.min(self.clip.frames.len() - 1)]
+    }
+}
+
+#[derive(Component)]
+pub struct MovementClips {
+    pub idle: AnimationClip,
+    pub walk: AnimationClip,
+}
+
+fn play_movement_clips(
+    mut anim_qry: Query<(
+        &mut SpriteAnimation,
+        &MovementClips,
+        Option<&KinematicCharacterControllerOutput>,
+    )>,
+) {
+    for (mut anim, movement_clips, kcc_out) in &mut anim_qry {
+        if anim.is_playing_one_shot() {
+            continue;
+        }
+        let moving = kcc_out.is_some_and(|kcc_out| kcc_out.effective_translation != Vec2::ZERO);
+        anim.play(if moving {
+            movement_clips.walk.clone()
+        } else {
+            movement_clips.idle.clone()
+        });
+    }
+}
+
+fn advance_sprite_animations(
+    mut anim_qry: Query<(&mut SpriteAnimation, &mut TextureAtlas)>,
+    time: Res<Time>,
+) {
+    let dt = time.delta();
+    for (mut anim, mut tex_atlas) in &mut anim_qry {
+        let finished_frames = anim.timer.tick(dt).times_finished_this_tick() as usize;
+        anim.frame = match anim.clip.mode {
+            AnimationMode::Looping => (anim.frame + finished_frames) % anim.clip.frames.len(),
+            AnimationMode::OneShot => (anim.frame + finished_frames).min(anim.clip.frames.len()),
+        };
+        let frame = anim.current_frame();
+        if tex_atlas.index != frame {
+            tex_atlas.index = frame;
+        }
+    }
+}
+
+#[derive(SystemSet, Hash, Debug, Clone, Eq, PartialEq)]
+pub struct AnimationSet;
+
+pub fn animation_plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (play_movement_clips, advance_sprite_animations)
+            .chain()
+            .in_set(AnimationSet)
+            .run_if(in_state(GameState::Playing)),
+    );
+}
@@ -1,9 +1,17 @@
-use {bevy::prelude::*, std::marker::PhantomData};
+use {
+    super::{
+        animation::{AnimationClip, SpriteAnimation},
+        shoebox::ShoeBoxAtlas,
+    },
+    bevy::{prelude::*, utils::HashMap},
+    std::marker::PhantomData,
+};
 
 #[derive(Resource)]
 pub struct TextureAtlasOwner<T> {
     tex: Handle<Image>,
     layout: Handle<TextureAtlasLayout>,
+    names: HashMap<String, usize>,
     _marker: PhantomData<T>,
 }
 
@@ -12,10 +20,24 @@ impl<T: Component> TextureAtlasOwner<T> {
         Self {
             tex,
             layout,
+            names: HashMap::new(),
             _marker: PhantomData,
         }
     }
 
+    pub fn from_shoebox(shoebox_atlas: &ShoeBoxAtlas) -> Self {
+        Self {
+            tex: shoebox_atlas.image.clone(),
+            layout: shoebox_atlas.layout.clone(),
+            names: shoebox_atlas.names().clone(),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn index(&self, name: &str) -> Option<usize> {
+        self.names.get(name).copied()
+    }
+
     pub fn texture(&self) -> Handle<Image> {
         self.tex.clone_weak()
     }
@@ -23,6 +45,16 @@ impl<T: Component> TextureAtlasOwner<T> {
     pub fn layout(&self) -> Handle<TextureAtlasLayout> {
         self.layout.clone_weak()
     }
+
+    pub fn animated(&self, clip: AnimationClip) -> (TextureAtlas, SpriteAnimation) {
+        (
+            TextureAtlas {
+                layout: self.layout(),
+                index: clip.first_frame(),
+            },
+            SpriteAnimation::new(clip),
+        )
+    }
 }
 
 #[derive(Resource)]
@@ -0,0 +1,297 @@
+use {
+    super::{
+        asset_owner::TextureAtlasOwner,
+        game_state::{GameState, SetupAssets},
+        health::{DamageEvent, DamageSource},
+        house::ZombieGoal,
+        movement::Movement,
+        player::Player,
+        primary_camera::PrimaryCamera,
+        telegraph,
+        throwable::Stunned,
+        tile::{Tile, AVG_TILE_DIMENSION, TILE_SIZE},
+        zombie::{
+            self, WaveStartedEvent, Zombie, ZombieHealth, ZombiePool, ZombiePopulation,
+            ZombieSpawns, ZOMBIE_MOVEMENT, ZOMBIE_RADIUS,
+        },
+    },
+    bevy::{
+        asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+        prelude::*,
+    },
+    bevy_rapier2d::prelude::*,
+    rand::{seq::SliceRandom, Rng},
+    serde::Deserialize,
+    std::io::{Error, ErrorKind},
+};
+
+#[derive(Deserialize, Clone, Copy)]
+pub enum BossAttack {
+    Charge { speed: f32, secs: f32 },
+    Slam { radius: f32 },
+    Summon { count: u32 },
+}
+
+#[derive(Deserialize, Clone)]
+pub struct BossPhase {
+    below_health: f32,
+    attack_secs: f32,
+    attacks: Vec<BossAttack>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct BossDef {
+    name: String,
+    wave: u32,
+    max_health: f32,
+    radius: f32,
+    speed: f32,
+    phases: Vec<BossPhase>,
+}
+
+const SLAM_KNOCKBACK: f32 = AVG_TILE_DIMENSION * 6.;
+
+//Boss definitions are read from bosses.ron so they can be tuned without rebuilding
+#[derive(Asset, TypePath, Deserialize)]
+pub struct BossDefs(Vec<BossDef>);
+
+#[derive(Resource)]
+struct Bosses(Handle<BossDefs>);
+
+#[derive(Default)]
+struct BossDefsLoader;
+
+impl AssetLoader for BossDefsLoader {
+    type Asset = BossDefs;
+    type Settings = ();
+    type Error = Error;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut boss_defs = String::new();
+        reader.read_to_string(&mut boss_defs).await?;
+        ron::from_str(&boss_defs).map_err(|err| Error::new(ErrorKind::InvalidData, err))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+#[derive(Component)]
+pub struct Boss {
+    def: BossDef,
+    phase: usize,
+    attack_timer: Timer,
+}
+
+impl Boss {
+    pub fn name(&self) -> &str {
+        &self.def.name
+    }
+}
+
+#[derive(Component)]
+pub struct Charging {
+    dir: Vec2,
+    speed: f32,
+    timer: Timer,
+}
+
+//Bosses rise through a spawn telegraph like the rest of the horde and take over the emerging zombie
+#[derive(Component)]
+pub struct BossTelegraph(BossDef);
+
+impl BossTelegraph {
+    pub fn boss_bundle(&self) -> impl Bundle {
+        let boss_def = &self.0;
+        (
+            Boss {
+                def: boss_def.clone(),
+                phase: 0,
+                attack_timer: Timer::from_seconds(
+                    boss_def.phases[0].attack_secs,
+                    TimerMode::Repeating,
+                ),
+            },
+            ZombieGoal::Player,
+            ZombieHealth::new(boss_def.max_health),
+            Collider::ball(boss_def.radius),
+            Sprite {
+                custom_size: Some(TILE_SIZE * boss_def.radius / ZOMBIE_RADIUS),
+                ..default()
+            },
+            Movement::new(ZOMBIE_MOVEMENT.with_max_speed(boss_def.speed)),
+        )
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_bosses(
+    mut cmds: Commands,
+    mut wave_started_evr: EventReader<WaveStartedEvent>,
+    mut zombie_pool: ResMut<ZombiePool>,
+    zombie_population: Res<ZombiePopulation>,
+    zombie_spawns: Res<ZombieSpawns>,
+    tile_tex_atlas: Res<TextureAtlasOwner<Tile>>,
+    bosses: Res<Bosses>,
+    boss_defs: Res<Assets<BossDefs>>,
+    player_qry: Query<&Transform, With<Player>>,
+    primary_cam_qry: Query<(&OrthographicProjection, &Transform), With<PrimaryCamera>>,
+) {
+    let mut thread_rng = rand::thread_rng();
+    let Some(boss_defs) = boss_defs.get(&bosses.0) else {
+        return;
+    };
+    for wave_started in wave_started_evr.read() {
+        for boss_def in boss_defs
+            .0
+            .iter()
+            .filter(|boss_def| boss_def.wave == wave_started.wave)
+        {
+            let Some(spawn_pos) = telegraph::pick_spawn_point(
+                &zombie_spawns.0,
+                player_qry.single().translation.truncate(),
+                telegraph::visible_area(&primary_cam_qry),
+                &mut thread_rng,
+            ) else {
+                return;
+            };
+            //With the population at its cap the boss is skipped rather than pushed past it
+            let Some(telegraph_id) = telegraph::spawn_telegraph(
+                &mut cmds,
+                &mut zombie_pool,
+                &zombie_population,
+                spawn_pos,
+                &tile_tex_atlas,
+            ) else {
+                return;
+            };
+            cmds.entity(telegraph_id)
+                .insert(BossTelegraph(boss_def.clone()));
+        }
+    }
+}
+
+fn advance_boss_phases(mut boss_qry: Query<(&mut Boss, &ZombieHealth)>) {
+    for (mut boss, boss_health) in &mut boss_qry {
+        let phase = boss
+            .def
+            .phases
+            .iter()
+            .rposition(|phase| boss_health.fraction() <= phase.below_health)
+            .unwrap_or(0);
+        if phase != boss.phase {
+            boss.phase = phase;
+            boss.attack_timer =
+                Timer::from_seconds(boss.def.phases[phase].attack_secs, TimerMode::Repeating);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn boss_attacks(
+    mut cmds: Commands,
+    mut boss_qry: Query<(Entity, &mut Boss, &Transform), (Without<Charging>, Without<Stunned>)>,
+    player_qry: Query<(Entity, &Transform), With<Player>>,
+    mut damage_evw: EventWriter<DamageEvent>,
+    mut zombie_pool: ResMut<ZombiePool>,
+    zombie_population: Res<ZombiePopulation>,
+    zombie_tex_atlas: Res<TextureAtlasOwner<Zombie>>,
+    time: Res<Time>,
+) {
+    let dt = time.delta();
+    let (player_id, player_xform) = player_qry.single();
+    let player_pos = player_xform.translation.truncate();
+    let mut thread_rng = rand::thread_rng();
+    for (boss_id, mut boss, boss_xform) in &mut boss_qry {
+        if !boss.attack_timer.tick(dt).just_finished() {
+            continue;
+        }
+        let boss_pos = boss_xform.translation.truncate();
+        let Some(&attack) = boss.def.phases[boss.phase].attacks.choose(&mut thread_rng) else {
+            continue;
+        };
+        match attack {
+            BossAttack::Charge { speed, secs } => {
+                cmds.entity(boss_id).insert(Charging {
+                    dir: (player_pos - boss_pos).normalize_or_zero(),
+                    speed,
+                    timer: Timer::from_seconds(secs, TimerMode::Once),
+                });
+            }
+            BossAttack::Slam { radius } => {
+                if boss_pos.distance(player_pos) <= radius {
+                    damage_evw.send(DamageEvent {
+                        target_id: player_id,
+                        half_hearts: 1,
+                        source: DamageSource::Zombie,
+                        knockback: (player_pos - boss_pos).normalize_or_zero() * SLAM_KNOCKBACK,
+                    });
+                }
+            }
+            BossAttack::Summon { count } => {
+                for _ in 0..count {
+                    zombie::spawn_zombie(
+                        &mut cmds,
+                        &mut zombie_pool,
+                        &zombie_population,
+                        boss_pos
+                            + Vec2::from_angle(thread_rng.gen_range(0.0..std::f32::consts::TAU))
+                                * AVG_TILE_DIMENSION,
+                        &zombie_tex_atlas,
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn boss_charge(
+    mut cmds: Commands,
+    mut charging_qry: Query<(
+        Entity,
+        &mut Charging,
+        &mut KinematicCharacterController,
+        &mut Transform,
+    )>,
+    time: Res<Time>,
+) {
+    for (charging_id, mut charging, mut charging_kcc, mut charging_xform) in &mut charging_qry {
+        if charging.timer.tick(time.delta()).finished() {
+            cmds.entity(charging_id).remove::<Charging>();
+            continue;
+        }
+        charging_xform.rotation = Quat::from_rotation_z(-charging.dir.angle_between(Vec2::X));
+        charging_kcc.translation = Some(charging.dir * charging.speed * time.delta_seconds());
+    }
+}
+
+pub fn boss_plugin(app: &mut App) {
+    app.init_asset::<BossDefs>()
+        .init_asset_loader::<BossDefsLoader>()
+        .add_systems(
+            OnEnter(GameState::Setup),
+            |mut cmds: Commands,
+             asset_server: Res<AssetServer>,
+             mut setup_assets: ResMut<SetupAssets>| {
+                let boss_defs = asset_server.load::<BossDefs>("bosses.ron");
+                setup_assets.track(boss_defs.clone());
+                cmds.insert_resource(Bosses(boss_defs));
+            },
+        )
+        .add_systems(
+            Update,
+            (spawn_bosses, advance_boss_phases, boss_attacks)
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(
+            FixedUpdate,
+            boss_charge.run_if(in_state(GameState::Playing)),
+        );
+}
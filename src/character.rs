@@ -0,0 +1,268 @@
+use {
+    super::{
+        asset_owner::TextureAtlasOwner,
+        combat::{WeaponDef, BAT, CROWBAR, KITCHEN_KNIFE, MACHINE_GUN, PISTOL, SILENCED_PISTOL},
+        game_state::GameState,
+        player::{Player, CHARACTER_POSES},
+        ui::{BUTTON_COLOR, HOVERED_BUTTON_COLOR},
+    },
+    bevy::prelude::*,
+};
+
+pub struct CharacterDef {
+    pub name: &'static str,
+    sprite: &'static str,
+    pub speed: f32,
+    pub weapon: &'static WeaponDef,
+}
+
+impl CharacterDef {
+    //Sprite names in the player atlas are the character's prefix followed by the pose
+    pub fn sprite(&self, pose: &str) -> String {
+        format!("{}_{pose}", self.sprite)
+    }
+}
+
+static CHARACTERS: [CharacterDef; 8] = [
+    CharacterDef {
+        name: "Hitman",
+        sprite: "hitman1",
+        speed: 1.,
+        weapon: &SILENCED_PISTOL,
+    },
+    CharacterDef {
+        name: "Office worker",
+        sprite: "manBlue",
+        speed: 1.05,
+        weapon: &PISTOL,
+    },
+    CharacterDef {
+        name: "Handyman",
+        sprite: "manBrown",
+        speed: 1.,
+        weapon: &CROWBAR,
+    },
+    CharacterDef {
+        name: "Grandpa",
+        sprite: "manOld",
+        speed: 0.85,
+        weapon: &BAT,
+    },
+    CharacterDef {
+        name: "Robot",
+        sprite: "robot1",
+        speed: 0.9,
+        weapon: &PISTOL,
+    },
+    CharacterDef {
+        name: "Soldier",
+        sprite: "soldier1",
+        speed: 0.9,
+        weapon: &MACHINE_GUN,
+    },
+    CharacterDef {
+        name: "Survivor",
+        sprite: "survivor1",
+        speed: 1.1,
+        weapon: &PISTOL,
+    },
+    CharacterDef {
+        name: "Chef",
+        sprite: "womanGreen",
+        speed: 1.15,
+        weapon: &KITCHEN_KNIFE,
+    },
+];
+
+#[derive(Resource)]
+pub struct SelectedCharacter(pub &'static CharacterDef);
+
+#[derive(Resource)]
+pub struct AvailableCharacters(Vec<&'static CharacterDef>);
+
+//A character missing any of its sprites is left off the roster instead of failing once it's picked
+pub fn available_characters(player_tex_atlas: &TextureAtlasOwner<Player>) -> AvailableCharacters {
+    AvailableCharacters(
+        CHARACTERS
+            .iter()
+            .filter(|character| {
+                let missing = CHARACTER_POSES
+                    .iter()
+                    .map(|pose| character.sprite(pose))
+                    .filter(|name| player_tex_atlas.index(name).is_none())
+                    .collect::<Vec<_>>();
+                if !missing.is_empty() {
+                    error!(
+                        "skipping {}, player atlas has no sprites named {}",
+                        character.name,
+                        missing.join(", ")
+                    );
+                }
+                missing.is_empty()
+            })
+            .collect(),
+    )
+}
+
+#[derive(Component)]
+struct CharacterButton(&'static CharacterDef);
+
+#[derive(Component)]
+struct ControlsButton;
+
+fn spawn_character_select(
+    mut cmds: Commands,
+    player_tex_atlas: Res<TextureAtlasOwner<Player>>,
+    available_characters: Res<AvailableCharacters>,
+) {
+    cmds.spawn((
+        StateScoped(GameState::CharacterSelect),
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Percent(4.),
+                ..default()
+            },
+            ..default()
+        },
+    ))
+    .with_children(|screen| {
+        screen.spawn(TextBundle::from_section(
+            "Choose your survivor",
+            TextStyle {
+                font_size: 40.,
+                ..default()
+            },
+        ));
+        screen
+            .spawn(NodeBundle {
+                style: Style {
+                    width: Val::Percent(90.),
+                    flex_wrap: FlexWrap::Wrap,
+                    justify_content: JustifyContent::Center,
+                    column_gap: Val::Percent(2.),
+                    row_gap: Val::Percent(2.),
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|roster| {
+                for &character in &available_characters.0 {
+                    roster
+                        .spawn((
+                            CharacterButton(character),
+                            ButtonBundle {
+                                style: Style {
+                                    width: Val::Percent(20.),
+                                    padding: UiRect::all(Val::Px(10.)),
+                                    flex_direction: FlexDirection::Column,
+                                    align_items: AlignItems::Center,
+                                    row_gap: Val::Px(6.),
+                                    ..default()
+                                },
+                                border_radius: BorderRadius::all(Val::Percent(10.)),
+                                background_color: BackgroundColor(BUTTON_COLOR),
+                                ..default()
+                            },
+                        ))
+                        .with_children(|button| {
+                            button.spawn((
+                                ImageBundle {
+                                    style: Style {
+                                        height: Val::Px(64.),
+                                        ..default()
+                                    },
+                                    image: UiImage {
+                                        texture: player_tex_atlas.texture(),
+                                        ..default()
+                                    },
+                                    ..default()
+                                },
+                                TextureAtlas {
+                                    layout: player_tex_atlas.layout(),
+                                    index: player_tex_atlas
+                                        .index(&character.sprite("stand"))
+                                        .unwrap_or_default(),
+                                },
+                            ));
+                            button.spawn(TextBundle::from_section(
+                                format!(
+                                    "{}\n{}\nSpeed {:.0}%",
+                                    character.name,
+                                    character.weapon.name,
+                                    character.speed * 100.
+                                ),
+                                TextStyle::default(),
+                            ));
+                        });
+                }
+            });
+        screen
+            .spawn((
+                ControlsButton,
+                ButtonBundle {
+                    style: Style {
+                        width: Val::Percent(20.),
+                        padding: UiRect::all(Val::Px(10.)),
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    border_radius: BorderRadius::all(Val::Percent(10.)),
+                    background_color: BackgroundColor(BUTTON_COLOR),
+                    ..default()
+                },
+            ))
+            .with_children(|button_label| {
+                button_label.spawn(TextBundle::from_section("Controls", TextStyle::default()));
+            });
+    });
+}
+
+fn select_character(
+    mut cmds: Commands,
+    mut button_qry: Query<
+        (&Interaction, &CharacterButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, character_button, mut button_color) in &mut button_qry {
+        match interaction {
+            Interaction::Pressed => {
+                cmds.insert_resource(SelectedCharacter(character_button.0));
+                next_state.set(GameState::Playing);
+            }
+            Interaction::Hovered => button_color.0 = HOVERED_BUTTON_COLOR,
+            Interaction::None => button_color.0 = BUTTON_COLOR,
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn open_controls(
+    mut button_qry: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<ControlsButton>),
+    >,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, mut button_color) in &mut button_qry {
+        match interaction {
+            Interaction::Pressed => next_state.set(GameState::Controls),
+            Interaction::Hovered => button_color.0 = HOVERED_BUTTON_COLOR,
+            Interaction::None => button_color.0 = BUTTON_COLOR,
+        }
+    }
+}
+
+pub fn character_plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::CharacterSelect), spawn_character_select)
+        .add_systems(
+            Update,
+            (select_character, open_controls).run_if(in_state(GameState::CharacterSelect)),
+        );
+}
@@ -0,0 +1,10 @@
+use bevy_rapier2d::prelude::*;
+
+pub const WALL_GROUP: Group = Group::GROUP_1;
+pub const PLAYER_GROUP: Group = Group::GROUP_2;
+pub const ZOMBIE_GROUP: Group = Group::GROUP_3;
+pub const SHOT_GROUP: Group = Group::GROUP_4;
+
+pub fn groups(memberships: Group) -> CollisionGroups {
+    CollisionGroups::new(memberships, Group::ALL)
+}
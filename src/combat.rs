@@ -0,0 +1,441 @@
+use {
+    super::{
+        aim::AimPosition,
+        game_state::GameState,
+        inventory::{Inventory, Item},
+        perception::{self, NoiseEvent},
+        player::{self, Player, PlayerAction},
+        stamina::Stamina,
+        steering::SpatialHash,
+        telegraph::Emerging,
+        tile::{Wall, AVG_TILE_DIMENSION, TILE_SIZE},
+        zombie::{Zombie, ZOMBIE_RADIUS},
+    },
+    bevy::prelude::*,
+    bevy_rapier2d::prelude::*,
+    leafwing_input_manager::prelude::*,
+    rand::Rng,
+    std::f32::consts::{FRAC_PI_3, FRAC_PI_4, FRAC_PI_6},
+};
+
+const MUZZLE_OFFSET: f32 = 20.;
+const KNOCKBACK_SECS: f32 = 0.2;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ShotKind {
+    Hitscan { range: f32 },
+    Projectile { speed: f32 },
+}
+
+#[derive(PartialEq)]
+pub enum WeaponKind {
+    Firearm {
+        spread: f32,
+        pellets: u32,
+        magazine: u32,
+        reload_secs: f32,
+        automatic: bool,
+        shot: ShotKind,
+    },
+    Melee {
+        reach: f32,
+        half_arc: f32,
+        knockback: f32,
+        stamina_cost: f32,
+    },
+}
+
+#[derive(PartialEq)]
+pub struct WeaponDef {
+    pub name: &'static str,
+    use_secs: f32,
+    pub damage: f32,
+    noise_radius: f32,
+    pub kind: WeaponKind,
+}
+
+pub static PISTOL: WeaponDef = WeaponDef {
+    name: "Pistol",
+    use_secs: 0.3,
+    damage: 1.,
+    noise_radius: AVG_TILE_DIMENSION * 10.,
+    kind: WeaponKind::Firearm {
+        spread: 0.03,
+        pellets: 1,
+        magazine: 12,
+        reload_secs: 1.2,
+        automatic: false,
+        shot: ShotKind::Hitscan {
+            range: TILE_SIZE.x * 12.,
+        },
+    },
+};
+
+pub static MACHINE_GUN: WeaponDef = WeaponDef {
+    name: "Machine gun",
+    use_secs: 0.08,
+    damage: 0.75,
+    noise_radius: AVG_TILE_DIMENSION * 14.,
+    kind: WeaponKind::Firearm {
+        spread: 0.12,
+        pellets: 1,
+        magazine: 30,
+        reload_secs: 2.,
+        automatic: true,
+        shot: ShotKind::Projectile {
+            speed: TILE_SIZE.x * 20.,
+        },
+    },
+};
+
+pub static SILENCED_PISTOL: WeaponDef = WeaponDef {
+    name: "Silenced pistol",
+    use_secs: 0.4,
+    damage: 1.,
+    noise_radius: AVG_TILE_DIMENSION * 2.,
+    kind: WeaponKind::Firearm {
+        spread: 0.02,
+        pellets: 1,
+        magazine: 10,
+        reload_secs: 1.4,
+        automatic: false,
+        shot: ShotKind::Hitscan {
+            range: TILE_SIZE.x * 12.,
+        },
+    },
+};
+
+pub static BAT: WeaponDef = WeaponDef {
+    name: "Bat",
+    use_secs: 0.6,
+    damage: 1.,
+    noise_radius: AVG_TILE_DIMENSION,
+    kind: WeaponKind::Melee {
+        reach: AVG_TILE_DIMENSION * 1.1,
+        half_arc: FRAC_PI_3,
+        knockback: AVG_TILE_DIMENSION * 4.,
+        stamina_cost: 20.,
+    },
+};
+
+pub static CROWBAR: WeaponDef = WeaponDef {
+    name: "Crowbar",
+    use_secs: 0.5,
+    damage: 1.5,
+    noise_radius: AVG_TILE_DIMENSION,
+    kind: WeaponKind::Melee {
+        reach: AVG_TILE_DIMENSION * 0.9,
+        half_arc: FRAC_PI_4,
+        knockback: AVG_TILE_DIMENSION * 2.,
+        stamina_cost: 15.,
+    },
+};
+
+pub static KITCHEN_KNIFE: WeaponDef = WeaponDef {
+    name: "Kitchen knife",
+    use_secs: 0.25,
+    damage: 0.75,
+    noise_radius: 0.,
+    kind: WeaponKind::Melee {
+        reach: AVG_TILE_DIMENSION * 0.6,
+        half_arc: FRAC_PI_6,
+        knockback: AVG_TILE_DIMENSION,
+        stamina_cost: 8.,
+    },
+};
+
+#[derive(Component)]
+pub struct EquippedWeapon {
+    def: &'static WeaponDef,
+    cooldown: Timer,
+    rounds: u32,
+    reload: Option<Timer>,
+}
+
+impl EquippedWeapon {
+    pub fn new(def: &'static WeaponDef) -> Self {
+        let mut weapon = Self::unloaded(def);
+        weapon.rounds = weapon.magazine_space();
+        weapon
+    }
+
+    pub fn unloaded(def: &'static WeaponDef) -> Self {
+        Self {
+            def,
+            cooldown: Timer::from_seconds(def.use_secs, TimerMode::Once),
+            rounds: 0,
+            reload: None,
+        }
+    }
+
+    pub fn def(&self) -> &'static WeaponDef {
+        self.def
+    }
+
+    //Loaded rounds, or None for melee weapons
+    pub fn ammo(&self) -> Option<u32> {
+        matches!(self.def.kind, WeaponKind::Firearm { .. }).then_some(self.rounds)
+    }
+
+    pub fn is_reloading(&self) -> bool {
+        self.reload.is_some()
+    }
+
+    pub fn magazine_space(&self) -> u32 {
+        match self.def.kind {
+            WeaponKind::Firearm { magazine, .. } => magazine - self.rounds,
+            WeaponKind::Melee { .. } => 0,
+        }
+    }
+
+    pub fn load(&mut self, rounds: u32) {
+        self.rounds += rounds.min(self.magazine_space());
+    }
+
+    pub fn unload(&mut self) -> u32 {
+        self.reload = None;
+        std::mem::take(&mut self.rounds)
+    }
+}
+
+#[derive(Component)]
+pub struct Knockback {
+    vel: Vec2,
+    timer: Timer,
+}
+
+impl Knockback {
+    pub fn new(vel: Vec2) -> Self {
+        Self {
+            vel,
+            timer: Timer::from_seconds(KNOCKBACK_SECS, TimerMode::Once),
+        }
+    }
+}
+
+#[derive(Event)]
+pub struct ShotFiredEvent {
+    pub shooter_id: Entity,
+    pub origin: Vec2,
+    pub dir: Vec2,
+    pub shot: ShotKind,
+    pub damage: f32,
+}
+
+#[derive(Event)]
+pub struct MeleeHitEvent {
+    pub target_id: Entity,
+    pub damage: f32,
+    pub knockback: Vec2,
+}
+
+//Reloads draw from the inventory's ammo, starting on demand or once the magazine runs dry
+//Entering a task cancels them
+fn reload_weapon(
+    mut player_qry: Query<(
+        &Player,
+        &ActionState<PlayerAction>,
+        &mut EquippedWeapon,
+        &mut Inventory,
+    )>,
+    time: Res<Time>,
+) {
+    let Ok((player, player_in, mut weapon, mut inventory)) = player_qry.get_single_mut() else {
+        return;
+    };
+    let WeaponKind::Firearm { reload_secs, .. } = weapon.def.kind else {
+        return;
+    };
+    if let Some(reload) = weapon.reload.as_mut() {
+        if player.doing_task() {
+            weapon.reload = None;
+        } else if reload.tick(time.delta()).finished() {
+            let rounds = inventory.take(Item::Ammo, weapon.magazine_space());
+            weapon.load(rounds);
+            weapon.reload = None;
+        }
+        return;
+    }
+    let wants_reload = weapon.rounds == 0 || player_in.just_pressed(&PlayerAction::Reload);
+    if wants_reload
+        && weapon.magazine_space() > 0
+        && inventory.contains(Item::Ammo)
+        && !player.doing_task()
+    {
+        weapon.reload = Some(Timer::from_seconds(reload_secs, TimerMode::Once));
+    }
+}
+
+//Pulling the trigger mid-reload abandons it as long as there are rounds left in the magazine
+fn fire_weapon(
+    mut player_qry: Query<(
+        Entity,
+        &Player,
+        &Transform,
+        &ActionState<PlayerAction>,
+        &mut EquippedWeapon,
+    )>,
+    aim_pos: Res<AimPosition>,
+    mut shot_fired_evw: EventWriter<ShotFiredEvent>,
+    mut noise_evw: EventWriter<NoiseEvent>,
+    time: Res<Time>,
+) {
+    let Ok((player_id, player, player_xform, player_in, mut weapon)) = player_qry.get_single_mut()
+    else {
+        return;
+    };
+    let WeaponKind::Firearm {
+        spread,
+        pellets,
+        automatic,
+        shot,
+        ..
+    } = weapon.def.kind
+    else {
+        return;
+    };
+    weapon.cooldown.tick(time.delta());
+
+    if player.doing_task() || !weapon.cooldown.finished() || weapon.rounds == 0 {
+        return;
+    }
+    let trigger_pulled = if automatic {
+        player_in.pressed(&PlayerAction::Fire)
+    } else {
+        player_in.just_pressed(&PlayerAction::Fire)
+    };
+    if !trigger_pulled || (weapon.is_reloading() && !player_in.just_pressed(&PlayerAction::Fire)) {
+        return;
+    }
+    weapon.reload = None;
+
+    let player_pos = player_xform.translation.truncate();
+    let Ok(aim_dir) = Dir2::new(aim_pos.as_vec() - player_pos) else {
+        return;
+    };
+    let origin = player_pos + *aim_dir * MUZZLE_OFFSET;
+    let mut thread_rng = rand::thread_rng();
+    for _ in 0..pellets {
+        let spread = thread_rng.gen_range(-spread..=spread);
+        shot_fired_evw.send(ShotFiredEvent {
+            shooter_id: player_id,
+            origin,
+            dir: Vec2::from_angle(spread).rotate(*aim_dir),
+            shot,
+            damage: weapon.def.damage,
+        });
+    }
+    noise_evw.send(NoiseEvent {
+        pos: player_pos,
+        radius: weapon.def.noise_radius,
+    });
+
+    weapon.cooldown.reset();
+    weapon.rounds -= 1;
+}
+
+//Hits every zombie within reach whose bearing is inside the arc around the player's facing
+#[allow(clippy::too_many_arguments)]
+fn swing_melee_weapon(
+    mut player_qry: Query<(
+        &Player,
+        &Transform,
+        &ActionState<PlayerAction>,
+        &mut EquippedWeapon,
+        &mut Stamina,
+    )>,
+    zombie_qry: Query<(), (With<Zombie>, Without<Emerging>)>,
+    wall_qry: Query<(), With<Wall>>,
+    spatial_hash: Res<SpatialHash>,
+    rapier_ctx: Res<RapierContext>,
+    mut melee_hit_evw: EventWriter<MeleeHitEvent>,
+    mut noise_evw: EventWriter<NoiseEvent>,
+    time: Res<Time>,
+) {
+    let Ok((player, player_xform, player_in, mut weapon, mut stamina)) =
+        player_qry.get_single_mut()
+    else {
+        return;
+    };
+    let WeaponKind::Melee {
+        reach,
+        half_arc,
+        knockback,
+        stamina_cost,
+    } = weapon.def.kind
+    else {
+        return;
+    };
+    if !weapon.cooldown.tick(time.delta()).finished()
+        || player.doing_task()
+        || !player_in.just_pressed(&PlayerAction::Fire)
+        || !stamina.spend(stamina_cost)
+    {
+        return;
+    }
+    weapon.cooldown.reset();
+
+    let player_pos = player_xform.translation.truncate();
+    let facing = (player_xform.rotation * Vec3::X).truncate();
+    for (zombie_id, zombie_pos) in spatial_hash.neighbours(player_pos, reach + ZOMBIE_RADIUS) {
+        let to_zombie = zombie_pos - player_pos;
+        if !zombie_qry.contains(zombie_id)
+            || facing.angle_between(to_zombie).abs() > half_arc
+            || !perception::can_see(&rapier_ctx, &wall_qry, player_pos, zombie_pos)
+        {
+            continue;
+        }
+        melee_hit_evw.send(MeleeHitEvent {
+            target_id: zombie_id,
+            damage: weapon.def.damage,
+            knockback: to_zombie.try_normalize().unwrap_or(facing) * knockback,
+        });
+    }
+    if weapon.def.noise_radius > 0. {
+        noise_evw.send(NoiseEvent {
+            pos: player_pos,
+            radius: weapon.def.noise_radius,
+        });
+    }
+}
+
+fn apply_knockback(
+    mut cmds: Commands,
+    mut knockback_qry: Query<(
+        Entity,
+        &mut Knockback,
+        Option<&mut KinematicCharacterController>,
+    )>,
+    time: Res<Time>,
+) {
+    for (knockback_id, mut knockback, kcc) in &mut knockback_qry {
+        if knockback.timer.tick(time.delta()).finished() {
+            cmds.entity(knockback_id).remove::<Knockback>();
+            continue;
+        }
+        if let Some(mut kcc) = kcc {
+            let falloff = knockback.timer.fraction_remaining();
+            kcc.translation = Some(knockback.vel * falloff * time.delta_seconds());
+        }
+    }
+}
+
+pub fn combat_plugin(app: &mut App) {
+    app.add_event::<ShotFiredEvent>()
+        .add_event::<MeleeHitEvent>()
+        .add_systems(
+            Update,
+            (
+                reload_weapon.before(fire_weapon),
+                fire_weapon,
+                swing_melee_weapon,
+            )
+                .after(player::player_task_input)
+                .run_if(in_state(GameState::Playing))
+                .run_if(player::can_act),
+        )
+        .add_systems(
+            FixedUpdate,
+            apply_knockback.run_if(in_state(GameState::Playing)),
+        );
+}
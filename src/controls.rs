@@ -0,0 +1,585 @@
+use {
+    super::{
+        game_state::GameState,
+        player::PlayerAction,
+        ui::{BUTTON_COLOR, HOVERED_BUTTON_COLOR},
+    },
+    bevy::{input::mouse::MouseWheel, prelude::*},
+    leafwing_input_manager::prelude::*,
+    serde::{Deserialize, Serialize},
+    std::{collections::HashMap, fs, path::PathBuf},
+    strum::IntoEnumIterator,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    ScrollUp,
+    ScrollDown,
+    Gamepad(GamepadButtonType),
+}
+
+impl Binding {
+    fn label(&self) -> String {
+        match self {
+            Self::Key(key) => {
+                let key = format!("{key:?}");
+                key.trim_start_matches("Key")
+                    .trim_start_matches("Digit")
+                    .to_string()
+            }
+            Self::Mouse(button) => format!("{button:?} mouse"),
+            Self::ScrollUp => "Scroll up".to_string(),
+            Self::ScrollDown => "Scroll down".to_string(),
+            Self::Gamepad(button) => format!("{button:?}"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Device {
+    KeyboardMouse,
+    Gamepad,
+}
+
+//Every action gets at most one keyboard or mouse binding and one gamepad button. The sticks
+//aren't rebindable and are always added when building the input map
+#[derive(Resource, Serialize, Deserialize)]
+pub struct Controls {
+    keyboard_mouse: HashMap<PlayerAction, Binding>,
+    gamepad: HashMap<PlayerAction, Binding>,
+}
+
+impl Default for Controls {
+    fn default() -> Self {
+        Self {
+            keyboard_mouse: HashMap::from([
+                (PlayerAction::MoveLeft, Binding::Key(KeyCode::KeyA)),
+                (PlayerAction::MoveRight, Binding::Key(KeyCode::KeyD)),
+                (PlayerAction::MoveUp, Binding::Key(KeyCode::KeyW)),
+                (PlayerAction::MoveDown, Binding::Key(KeyCode::KeyS)),
+                (PlayerAction::ExitTask, Binding::Key(KeyCode::Escape)),
+                (PlayerAction::Reload, Binding::Key(KeyCode::KeyR)),
+                (PlayerAction::Sprint, Binding::Key(KeyCode::ShiftLeft)),
+                (PlayerAction::Dodge, Binding::Key(KeyCode::Space)),
+                (PlayerAction::PickUp, Binding::Key(KeyCode::KeyE)),
+                (PlayerAction::Hotbar1, Binding::Key(KeyCode::Digit1)),
+                (PlayerAction::Hotbar2, Binding::Key(KeyCode::Digit2)),
+                (PlayerAction::Hotbar3, Binding::Key(KeyCode::Digit3)),
+                (PlayerAction::Hotbar4, Binding::Key(KeyCode::Digit4)),
+                (PlayerAction::Hotbar5, Binding::Key(KeyCode::Digit5)),
+                (PlayerAction::Hotbar6, Binding::Key(KeyCode::Digit6)),
+                (PlayerAction::EnterTask, Binding::Mouse(MouseButton::Left)),
+                (PlayerAction::Fire, Binding::Mouse(MouseButton::Left)),
+                (PlayerAction::NextItem, Binding::ScrollDown),
+                (PlayerAction::PreviousItem, Binding::ScrollUp),
+            ]),
+            gamepad: HashMap::from([
+                (
+                    PlayerAction::EnterTask,
+                    Binding::Gamepad(GamepadButtonType::South),
+                ),
+                (
+                    PlayerAction::PickUp,
+                    Binding::Gamepad(GamepadButtonType::South),
+                ),
+                (
+                    PlayerAction::ExitTask,
+                    Binding::Gamepad(GamepadButtonType::East),
+                ),
+                (
+                    PlayerAction::Reload,
+                    Binding::Gamepad(GamepadButtonType::West),
+                ),
+                (
+                    PlayerAction::Fire,
+                    Binding::Gamepad(GamepadButtonType::RightTrigger2),
+                ),
+                (
+                    PlayerAction::Dodge,
+                    Binding::Gamepad(GamepadButtonType::LeftTrigger2),
+                ),
+                (
+                    PlayerAction::Sprint,
+                    Binding::Gamepad(GamepadButtonType::LeftThumb),
+                ),
+                (
+                    PlayerAction::NextItem,
+                    Binding::Gamepad(GamepadButtonType::RightTrigger),
+                ),
+                (
+                    PlayerAction::PreviousItem,
+                    Binding::Gamepad(GamepadButtonType::LeftTrigger),
+                ),
+            ]),
+        }
+    }
+}
+
+impl Controls {
+    fn config_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|config_dir| config_dir.join("calamity").join("controls.ron"))
+    }
+
+    //A missing config just means the defaults were never changed
+    fn load() -> Self {
+        let Some(path) = Self::config_path() else {
+            return Self::default();
+        };
+        let Ok(config) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+        ron::from_str(&config).unwrap_or_else(|err| {
+            warn!("ignoring invalid controls in {}: {err}", path.display());
+            Self::default()
+        })
+    }
+
+    fn save(&self) {
+        let Some(path) = Self::config_path() else {
+            warn!("no config directory to save controls to");
+            return;
+        };
+        let saved = ron::ser::to_string_pretty(self, default())
+            .map_err(|err| err.to_string())
+            .and_then(|config| {
+                path.parent()
+                    .map_or(Ok(()), fs::create_dir_all)
+                    .and_then(|_| fs::write(&path, config))
+                    .map_err(|err| err.to_string())
+            });
+        if let Err(err) = saved {
+            warn!("couldn't save controls to {}: {err}", path.display());
+        }
+    }
+
+    fn bindings(&self, device: Device) -> &HashMap<PlayerAction, Binding> {
+        match device {
+            Device::KeyboardMouse => &self.keyboard_mouse,
+            Device::Gamepad => &self.gamepad,
+        }
+    }
+
+    fn bindings_mut(&mut self, device: Device) -> &mut HashMap<PlayerAction, Binding> {
+        match device {
+            Device::KeyboardMouse => &mut self.keyboard_mouse,
+            Device::Gamepad => &mut self.gamepad,
+        }
+    }
+
+    //Starting a task deliberately shares its binding with firing and picking up
+    fn conflict(
+        &self,
+        action: &PlayerAction,
+        device: Device,
+        binding: Binding,
+    ) -> Option<&PlayerAction> {
+        let shared = |other: &PlayerAction| {
+            matches!(
+                (action, other),
+                (
+                    PlayerAction::EnterTask,
+                    PlayerAction::Fire | PlayerAction::PickUp
+                ) | (
+                    PlayerAction::Fire | PlayerAction::PickUp,
+                    PlayerAction::EnterTask
+                )
+            )
+        };
+        self.bindings(device)
+            .iter()
+            .find(|&(other, &other_binding)| {
+                other != action && other_binding == binding && !shared(other)
+            })
+            .map(|(other, _)| other)
+    }
+
+    pub fn input_map(&self) -> InputMap<PlayerAction> {
+        let mut input_map = InputMap::default()
+            .with_dual_axis(PlayerAction::Move, GamepadStick::LEFT)
+            .with_dual_axis(PlayerAction::Aim, GamepadStick::RIGHT);
+        for (action, binding) in self.keyboard_mouse.iter().chain(&self.gamepad) {
+            let action = action.clone();
+            match *binding {
+                Binding::Key(key) => input_map.insert(action, key),
+                Binding::Mouse(button) => input_map.insert(action, button),
+                Binding::ScrollUp => input_map.insert(action, MouseScrollDirection::UP),
+                Binding::ScrollDown => input_map.insert(action, MouseScrollDirection::DOWN),
+                Binding::Gamepad(button) => input_map.insert(action, button),
+            };
+        }
+        input_map
+    }
+}
+
+//Waiting for the next input to bind. Capturing is armed a frame after the binding is clicked so
+//the click itself isn't taken as the new binding, and cancelled by a timeout rather than a key so
+//that every key, Escape included, stays bindable
+#[derive(Resource)]
+struct Rebinding {
+    action: PlayerAction,
+    device: Device,
+    armed: bool,
+    timeout: Timer,
+}
+
+#[derive(Component)]
+struct BindingButton {
+    action: PlayerAction,
+    device: Device,
+}
+
+#[derive(Component, Clone, Copy)]
+enum ControlsButton {
+    Reset,
+    Back,
+}
+
+impl ControlsButton {
+    fn label(&self) -> &str {
+        match self {
+            Self::Reset => "Reset to defaults",
+            Self::Back => "Back",
+        }
+    }
+}
+
+#[derive(Component)]
+struct ControlsStatusText;
+
+const IDLE_STATUS: &str = "Click a binding to change it";
+const CAPTURE_TIMEOUT_SECS: f32 = 5.;
+
+fn spawn_controls_screen(mut cmds: Commands) {
+    let button_bundle = |width| ButtonBundle {
+        style: Style {
+            width: Val::Percent(width),
+            padding: UiRect::all(Val::Px(4.)),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        border_radius: BorderRadius::all(Val::Percent(10.)),
+        background_color: BackgroundColor(BUTTON_COLOR),
+        ..default()
+    };
+    let small_text = TextStyle {
+        font_size: 16.,
+        ..default()
+    };
+    cmds.spawn((
+        StateScoped(GameState::Controls),
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Percent(2.),
+                ..default()
+            },
+            ..default()
+        },
+    ))
+    .with_children(|screen| {
+        screen.spawn(TextBundle::from_section(
+            "Controls",
+            TextStyle {
+                font_size: 40.,
+                ..default()
+            },
+        ));
+        screen.spawn((
+            ControlsStatusText,
+            TextBundle::from_section(IDLE_STATUS, TextStyle::default()),
+        ));
+        screen
+            .spawn(NodeBundle {
+                style: Style {
+                    width: Val::Percent(90.),
+                    flex_wrap: FlexWrap::Wrap,
+                    justify_content: JustifyContent::SpaceBetween,
+                    row_gap: Val::Px(4.),
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|actions| {
+                for action in PlayerAction::iter().filter(PlayerAction::is_rebindable) {
+                    actions
+                        .spawn(NodeBundle {
+                            style: Style {
+                                width: Val::Percent(48.),
+                                align_items: AlignItems::Center,
+                                column_gap: Val::Percent(2.),
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|row| {
+                            row.spawn(
+                                TextBundle::from_section(action.label(), small_text.clone())
+                                    .with_style(Style {
+                                        width: Val::Percent(36.),
+                                        ..default()
+                                    }),
+                            );
+                            for device in [Device::KeyboardMouse, Device::Gamepad] {
+                                row.spawn((
+                                    BindingButton {
+                                        action: action.clone(),
+                                        device,
+                                    },
+                                    button_bundle(30.),
+                                ))
+                                .with_children(|button_label| {
+                                    button_label
+                                        .spawn(TextBundle::from_section("", small_text.clone()));
+                                });
+                            }
+                        });
+                }
+            });
+        for button in [ControlsButton::Reset, ControlsButton::Back] {
+            screen
+                .spawn((button, button_bundle(20.)))
+                .with_children(|button_label| {
+                    button_label.spawn(TextBundle::from_section(
+                        button.label(),
+                        TextStyle::default(),
+                    ));
+                });
+        }
+    });
+}
+
+//Presses are ignored while capturing, since the capture is what consumes them
+#[allow(clippy::type_complexity)]
+fn press_controls_buttons(
+    mut cmds: Commands,
+    mut binding_button_qry: Query<
+        (&Interaction, &BindingButton, &mut BackgroundColor),
+        (Changed<Interaction>, Without<ControlsButton>),
+    >,
+    mut controls_button_qry: Query<
+        (&Interaction, &ControlsButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    rebinding: Option<Res<Rebinding>>,
+    mut controls: ResMut<Controls>,
+    mut status_qry: Query<&mut Text, With<ControlsStatusText>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let capturing = rebinding.is_some();
+    for (interaction, binding_button, mut button_color) in &mut binding_button_qry {
+        match interaction {
+            Interaction::Pressed if !capturing => {
+                cmds.insert_resource(Rebinding {
+                    action: binding_button.action.clone(),
+                    device: binding_button.device,
+                    armed: false,
+                    timeout: Timer::from_seconds(CAPTURE_TIMEOUT_SECS, TimerMode::Once),
+                });
+                status_qry.single_mut().sections[0].value = format!(
+                    "Press a {} for {}, or wait {CAPTURE_TIMEOUT_SECS} seconds to cancel",
+                    match binding_button.device {
+                        Device::KeyboardMouse => "key or mouse button",
+                        Device::Gamepad => "gamepad button",
+                    },
+                    binding_button.action.label()
+                );
+            }
+            Interaction::Pressed => (),
+            Interaction::Hovered => button_color.0 = HOVERED_BUTTON_COLOR,
+            Interaction::None => button_color.0 = BUTTON_COLOR,
+        }
+    }
+    for (interaction, button, mut button_color) in &mut controls_button_qry {
+        match (interaction, button) {
+            (Interaction::Pressed, _) if capturing => (),
+            (Interaction::Pressed, ControlsButton::Reset) => {
+                *controls = Controls::default();
+                controls.save();
+                status_qry.single_mut().sections[0].value = IDLE_STATUS.to_string();
+            }
+            (Interaction::Pressed, ControlsButton::Back) => {
+                next_state.set(GameState::CharacterSelect)
+            }
+            (Interaction::Hovered, _) => button_color.0 = HOVERED_BUTTON_COLOR,
+            (Interaction::None, _) => button_color.0 = BUTTON_COLOR,
+        }
+    }
+}
+
+//A binding already used by another action is refused, and capturing carries on until a free one
+//is pressed. Accepted bindings are saved straight away
+#[allow(clippy::too_many_arguments)]
+fn capture_binding(
+    mut cmds: Commands,
+    rebinding: Option<ResMut<Rebinding>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_btns: Res<ButtonInput<MouseButton>>,
+    mut mouse_wheel_evr: EventReader<MouseWheel>,
+    gamepad_btns: Res<ButtonInput<GamepadButton>>,
+    mut controls: ResMut<Controls>,
+    mut status_qry: Query<&mut Text, With<ControlsStatusText>>,
+    time: Res<Time>,
+) {
+    let scroll = mouse_wheel_evr.read().map(|wheel| wheel.y).sum::<f32>();
+    let Some(mut rebinding) = rebinding else {
+        return;
+    };
+    if !rebinding.armed {
+        rebinding.armed = true;
+        return;
+    }
+    if rebinding.timeout.tick(time.delta()).finished() {
+        cmds.remove_resource::<Rebinding>();
+        status_qry.single_mut().sections[0].value = IDLE_STATUS.to_string();
+        return;
+    }
+    let binding = match rebinding.device {
+        Device::KeyboardMouse => keys
+            .get_just_pressed()
+            .next()
+            .map(|&key| Binding::Key(key))
+            .or_else(|| {
+                mouse_btns
+                    .get_just_pressed()
+                    .next()
+                    .map(|&button| Binding::Mouse(button))
+            })
+            .or_else(|| (scroll > 0.).then_some(Binding::ScrollUp))
+            .or_else(|| (scroll < 0.).then_some(Binding::ScrollDown)),
+        Device::Gamepad => gamepad_btns
+            .get_just_pressed()
+            .next()
+            .map(|button| Binding::Gamepad(button.button_type)),
+    };
+    let Some(binding) = binding else {
+        return;
+    };
+    let mut status = status_qry.single_mut();
+    if let Some(other) = controls.conflict(&rebinding.action, rebinding.device, binding) {
+        rebinding.timeout.reset();
+        status.sections[0].value = format!(
+            "{} is already bound to {}, press another",
+            binding.label(),
+            other.label()
+        );
+        return;
+    }
+    controls
+        .bindings_mut(rebinding.device)
+        .insert(rebinding.action.clone(), binding);
+    controls.save();
+    cmds.remove_resource::<Rebinding>();
+    status.sections[0].value = IDLE_STATUS.to_string();
+}
+
+fn update_binding_labels(
+    controls: Res<Controls>,
+    rebinding: Option<Res<Rebinding>>,
+    binding_button_qry: Query<(&BindingButton, &Children)>,
+    mut text_qry: Query<&mut Text>,
+) {
+    for (binding_button, children) in &binding_button_qry {
+        let Ok(mut label) = text_qry.get_mut(children[0]) else {
+            continue;
+        };
+        let capturing = rebinding.as_ref().is_some_and(|rebinding| {
+            rebinding.action == binding_button.action && rebinding.device == binding_button.device
+        });
+        label.sections[0].value = if capturing {
+            "...".to_string()
+        } else {
+            controls
+                .bindings(binding_button.device)
+                .get(&binding_button.action)
+                .map_or("-".to_string(), Binding::label)
+        };
+    }
+}
+
+pub fn controls_plugin(app: &mut App) {
+    app.insert_resource(Controls::load())
+        .add_systems(OnEnter(GameState::Controls), spawn_controls_screen)
+        .add_systems(OnExit(GameState::Controls), |mut cmds: Commands| {
+            cmds.remove_resource::<Rebinding>();
+        })
+        .add_systems(
+            Update,
+            (
+                press_controls_buttons,
+                capture_binding,
+                update_binding_labels,
+            )
+                .chain()
+                .run_if(in_state(GameState::Controls)),
+        );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_have_no_conflicts() {
+        let controls = Controls::default();
+        for device in [Device::KeyboardMouse, Device::Gamepad] {
+            for (action, &binding) in controls.bindings(device) {
+                assert_eq!(controls.conflict(action, device, binding), None);
+            }
+        }
+    }
+
+    #[test]
+    fn a_binding_used_by_another_action_conflicts() {
+        let controls = Controls::default();
+        let key_a = Binding::Key(KeyCode::KeyA);
+        assert_eq!(
+            controls.conflict(&PlayerAction::Reload, Device::KeyboardMouse, key_a),
+            Some(&PlayerAction::MoveLeft)
+        );
+        assert_eq!(
+            controls.conflict(&PlayerAction::MoveLeft, Device::KeyboardMouse, key_a),
+            None
+        );
+        assert_eq!(
+            controls.conflict(
+                &PlayerAction::Reload,
+                Device::KeyboardMouse,
+                Binding::Key(KeyCode::KeyQ)
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn devices_are_checked_separately() {
+        let controls = Controls::default();
+        let south = Binding::Gamepad(GamepadButtonType::South);
+        assert_eq!(
+            controls.conflict(&PlayerAction::Reload, Device::KeyboardMouse, south),
+            None
+        );
+        assert!(controls
+            .conflict(&PlayerAction::Reload, Device::Gamepad, south)
+            .is_some());
+    }
+
+    #[test]
+    fn only_starting_a_task_shares_its_binding() {
+        let controls = Controls::default();
+        let left_click = Binding::Mouse(MouseButton::Left);
+        assert_eq!(
+            controls.conflict(&PlayerAction::EnterTask, Device::KeyboardMouse, left_click),
+            None
+        );
+        assert_eq!(
+            controls.conflict(&PlayerAction::PickUp, Device::KeyboardMouse, left_click),
+            Some(&PlayerAction::Fire)
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use {
+    super::{
+        game_state::GameState,
+        health::Invulnerable,
+        movement::Movement,
+        player::{self, Player, PlayerAction},
+        tile::AVG_TILE_DIMENSION,
+    },
+    bevy::prelude::*,
+    bevy_rapier2d::prelude::*,
+    leafwing_input_manager::prelude::*,
+    std::f32::consts::{PI, TAU},
+};
+
+const DODGE_DISTANCE: f32 = AVG_TILE_DIMENSION * 2.;
+const DODGE_SECS: f32 = 0.3;
+const DODGE_INVULNERABLE_SECS: f32 = 0.4;
+const DODGE_COOLDOWN_SECS: f32 = 1.2;
+
+#[derive(Component)]
+pub struct Dodge {
+    dir: Vec2,
+    timer: Timer,
+}
+
+#[derive(Component)]
+pub struct DodgeCooldown(Timer);
+
+impl Default for DodgeCooldown {
+    fn default() -> Self {
+        let mut cooldown = Timer::from_seconds(DODGE_COOLDOWN_SECS, TimerMode::Once);
+        cooldown.tick(cooldown.duration());
+        Self(cooldown)
+    }
+}
+
+//Rolls the way the player is moving, or the way they're facing when standing still
+#[allow(clippy::type_complexity)]
+fn start_dodge(
+    mut cmds: Commands,
+    mut player_qry: Query<
+        (
+            Entity,
+            &Player,
+            &Transform,
+            &Movement,
+            &ActionState<PlayerAction>,
+            &mut DodgeCooldown,
+        ),
+        Without<Dodge>,
+    >,
+    time: Res<Time>,
+) {
+    let Ok((player_id, player, player_xform, player_movement, player_in, mut dodge_cooldown)) =
+        player_qry.get_single_mut()
+    else {
+        return;
+    };
+    if !dodge_cooldown.0.tick(time.delta()).finished()
+        || player.doing_task()
+        || !player_in.just_pressed(&PlayerAction::Dodge)
+    {
+        return;
+    }
+    dodge_cooldown.0.reset();
+    let dir = player_movement
+        .velocity()
+        .try_normalize()
+        .unwrap_or((player_xform.rotation * Vec3::X).truncate());
+    cmds.entity(player_id).insert((
+        Dodge {
+            dir,
+            timer: Timer::from_seconds(DODGE_SECS, TimerMode::Once),
+        },
+        Invulnerable::new(DODGE_INVULNERABLE_SECS),
+    ));
+}
+
+//The roll goes through the character controller so walls stop it, spinning and squashing the sprite
+fn dodge_movement(
+    mut cmds: Commands,
+    mut dodge_qry: Query<(
+        Entity,
+        &mut Dodge,
+        &mut KinematicCharacterController,
+        &mut Transform,
+    )>,
+    time: Res<Time>,
+) {
+    for (dodge_id, mut dodge, mut dodge_kcc, mut dodge_xform) in &mut dodge_qry {
+        if dodge.timer.tick(time.delta()).finished() {
+            dodge_xform.scale = Vec3::ONE;
+            cmds.entity(dodge_id).remove::<Dodge>();
+            continue;
+        }
+        let t = dodge.timer.fraction();
+        dodge_kcc.translation =
+            Some(dodge.dir * DODGE_DISTANCE / DODGE_SECS * time.delta_seconds());
+        dodge_xform.rotation = Quat::from_rotation_z(-dodge.dir.angle_between(Vec2::X) + TAU * t);
+        dodge_xform.scale = Vec3::splat(1. - (t * PI).sin() * 0.25);
+    }
+}
+
+pub fn dodge_plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        start_dodge
+            .after(player::player_task_input)
+            .run_if(in_state(GameState::Playing))
+            .run_if(player::can_act),
+    )
+    .add_systems(
+        FixedUpdate,
+        dodge_movement
+            .before(player::player_movement)
+            .run_if(in_state(GameState::Playing)),
+    );
+}
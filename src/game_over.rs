@@ -0,0 +1,193 @@
+use {
+    super::{
+        game_state::GameState,
+        health::PlayerHealthBar,
+        player::{self, Player},
+        task::TaskCompletedEvent,
+        ui::{BUTTON_COLOR, HOVERED_BUTTON_COLOR},
+        zombie::ZombieKilledEvent,
+    },
+    bevy::{app::AppExit, prelude::*},
+    std::f32::consts::FRAC_PI_2,
+};
+
+const DEATH_SECS: f32 = 1.5;
+const DEATH_COLOR: Color = Color::srgb(0.6, 0.1, 0.1);
+
+#[derive(Resource, Default)]
+struct RunStats {
+    secs_survived: f32,
+    tasks_completed: u32,
+    zombies_killed: u32,
+}
+
+#[derive(Component)]
+struct Dying {
+    timer: Timer,
+    start_rotation: Quat,
+}
+
+#[derive(Component, Clone, Copy)]
+enum GameOverButton {
+    Restart,
+    Quit,
+}
+
+impl GameOverButton {
+    fn label(&self) -> &str {
+        match self {
+            Self::Restart => "Restart",
+            Self::Quit => "Quit",
+        }
+    }
+}
+
+fn track_run_stats(
+    mut run_stats: ResMut<RunStats>,
+    mut task_completed_evr: EventReader<TaskCompletedEvent>,
+    mut zombie_killed_evr: EventReader<ZombieKilledEvent>,
+    time: Res<Time>,
+) {
+    run_stats.secs_survived += time.delta_seconds();
+    run_stats.tasks_completed += task_completed_evr.read().count() as u32;
+    run_stats.zombies_killed += zombie_killed_evr.read().count() as u32;
+}
+
+#[allow(clippy::type_complexity)]
+fn start_dying(
+    mut cmds: Commands,
+    player_qry: Query<(Entity, &PlayerHealthBar, &Transform), (With<Player>, Without<Dying>)>,
+) {
+    let Ok((player_id, player_hp_bar, player_xform)) = player_qry.get_single() else {
+        return;
+    };
+    if player_hp_bar.is_empty() {
+        cmds.entity(player_id).insert(Dying {
+            timer: Timer::from_seconds(DEATH_SECS, TimerMode::Once),
+            start_rotation: player_xform.rotation,
+        });
+    }
+}
+
+//The player falls over and fades to red before the world is torn down for the summary screen
+fn play_death_animation(
+    mut dying_qry: Query<(&mut Dying, &mut Transform, &mut Sprite)>,
+    mut next_state: ResMut<NextState<GameState>>,
+    time: Res<Time>,
+) {
+    for (mut dying, mut dying_xform, mut dying_sprite) in &mut dying_qry {
+        let t = dying.timer.tick(time.delta()).fraction();
+        dying_xform.rotation = dying.start_rotation * Quat::from_rotation_z(FRAC_PI_2 * t);
+        dying_xform.scale = Vec3::splat(1. - t * 0.2);
+        dying_sprite.color = Color::WHITE.mix(&DEATH_COLOR, t).with_alpha(1. - t * 0.5);
+        if dying.timer.finished() {
+            next_state.set(GameState::GameOver);
+        }
+    }
+}
+
+fn spawn_game_over_screen(mut cmds: Commands, run_stats: Res<RunStats>) {
+    let secs_survived = run_stats.secs_survived as u32;
+    cmds.spawn((
+        StateScoped(GameState::GameOver),
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Percent(4.),
+                ..default()
+            },
+            background_color: BackgroundColor(Color::srgba(0., 0., 0., 0.6)),
+            ..default()
+        },
+    ))
+    .with_children(|screen| {
+        screen.spawn(TextBundle::from_section(
+            "You died",
+            TextStyle {
+                font_size: 40.,
+                ..default()
+            },
+        ));
+        screen.spawn(
+            TextBundle::from_section(
+                format!(
+                    "Survived {}:{:02}\nTasks completed {}\nZombies killed {}",
+                    secs_survived / 60,
+                    secs_survived % 60,
+                    run_stats.tasks_completed,
+                    run_stats.zombies_killed,
+                ),
+                TextStyle::default(),
+            )
+            .with_text_justify(JustifyText::Center),
+        );
+        for button in [GameOverButton::Restart, GameOverButton::Quit] {
+            screen
+                .spawn((
+                    button,
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Percent(20.),
+                            padding: UiRect::all(Val::Px(10.)),
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        border_radius: BorderRadius::all(Val::Percent(10.)),
+                        background_color: BackgroundColor(BUTTON_COLOR),
+                        ..default()
+                    },
+                ))
+                .with_children(|button_label| {
+                    button_label.spawn(TextBundle::from_section(
+                        button.label(),
+                        TextStyle::default(),
+                    ));
+                });
+        }
+    });
+}
+
+//Restarting goes straight back to playing as the same character with a freshly spawned level
+fn press_game_over_buttons(
+    mut button_qry: Query<
+        (&Interaction, &GameOverButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut app_exit_evw: EventWriter<AppExit>,
+) {
+    for (interaction, button, mut button_color) in &mut button_qry {
+        match (interaction, button) {
+            (Interaction::Pressed, GameOverButton::Restart) => next_state.set(GameState::Playing),
+            (Interaction::Pressed, GameOverButton::Quit) => {
+                app_exit_evw.send(AppExit::Success);
+            }
+            (Interaction::Hovered, _) => button_color.0 = HOVERED_BUTTON_COLOR,
+            (Interaction::None, _) => button_color.0 = BUTTON_COLOR,
+        }
+    }
+}
+
+pub fn game_over_plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::Playing), |mut cmds: Commands| {
+        cmds.insert_resource(RunStats::default());
+    })
+    .add_systems(
+        Update,
+        (
+            track_run_stats.run_if(player::is_alive),
+            start_dying,
+            play_death_animation.after(start_dying),
+        )
+            .run_if(in_state(GameState::Playing)),
+    )
+    .add_systems(OnEnter(GameState::GameOver), spawn_game_over_screen)
+    .add_systems(
+        Update,
+        press_game_over_buttons.run_if(in_state(GameState::GameOver)),
+    );
+}
@@ -3,15 +3,39 @@ use bevy::prelude::*;
 #[derive(States, Hash, Debug, Clone, Eq, PartialEq)]
 pub enum GameState {
     Setup,
+    CharacterSelect,
+    Controls,
     Playing,
+    GameOver,
+}
+
+//Assets that must finish loading before setup ends, like sprite sheets read by name on spawn
+#[derive(Resource, Default)]
+pub struct SetupAssets(Vec<UntypedHandle>);
+
+impl SetupAssets {
+    pub fn track<A: Asset>(&mut self, handle: Handle<A>) {
+        self.0.push(handle.untyped());
+    }
 }
 
 pub fn game_state_plugin(app: &mut App) {
     app.insert_state(GameState::Setup)
         .enable_state_scoped_entities::<GameState>()
+        .init_resource::<SetupAssets>()
         .add_systems(
             Update,
-            (|mut next_state: ResMut<NextState<GameState>>| next_state.set(GameState::Playing))
-                .run_if(in_state(GameState::Setup)),
+            (|setup_assets: Res<SetupAssets>,
+              asset_server: Res<AssetServer>,
+              mut next_state: ResMut<NextState<GameState>>| {
+                if setup_assets
+                    .0
+                    .iter()
+                    .all(|handle| asset_server.is_loaded_with_dependencies(handle.id()))
+                {
+                    next_state.set(GameState::CharacterSelect)
+                }
+            })
+            .run_if(in_state(GameState::Setup)),
         );
 }
@@ -0,0 +1,116 @@
+use {
+    super::{combat::Knockback, game_state::GameState},
+    bevy::prelude::*,
+};
+
+const HIT_INVULNERABLE_SECS: f32 = 0.8;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DamageSource {
+    Zombie,
+    TaskFailure,
+    Hazard,
+}
+
+#[derive(Event)]
+pub struct DamageEvent {
+    pub target_id: Entity,
+    pub half_hearts: u8,
+    pub source: DamageSource,
+    pub knockback: Vec2,
+}
+
+//Hearts are stored as a half heart count and read back as 2 (full), 1 (half) or 0 (empty) per heart
+#[derive(Component)]
+pub struct PlayerHealthBar {
+    half_hearts: u8,
+}
+
+impl Default for PlayerHealthBar {
+    fn default() -> Self {
+        Self {
+            half_hearts: Self::MAX_HALF_HEARTS,
+        }
+    }
+}
+
+impl PlayerHealthBar {
+    pub const MAX_SIZE: usize = 3;
+    const MAX_HALF_HEARTS: u8 = Self::MAX_SIZE as u8 * 2;
+
+    pub fn heart(&self, idx: usize) -> u8 {
+        self.half_hearts.saturating_sub(idx as u8 * 2).min(2)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.half_hearts == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.half_hearts == Self::MAX_HALF_HEARTS
+    }
+
+    pub fn heal(&mut self, half_hearts: u8) {
+        self.half_hearts = (self.half_hearts + half_hearts).min(Self::MAX_HALF_HEARTS);
+    }
+
+    fn damage(&mut self, half_hearts: u8) {
+        self.half_hearts = self.half_hearts.saturating_sub(half_hearts);
+    }
+}
+
+#[derive(Component)]
+pub struct Invulnerable(Timer);
+
+impl Invulnerable {
+    pub fn new(secs: f32) -> Self {
+        Self(Timer::from_seconds(secs, TimerMode::Once))
+    }
+}
+
+fn expire_invulnerability(
+    mut cmds: Commands,
+    mut invulnerable_qry: Query<(Entity, &mut Invulnerable)>,
+    time: Res<Time>,
+) {
+    for (invulnerable_id, mut invulnerable) in &mut invulnerable_qry {
+        if invulnerable.0.tick(time.delta()).finished() {
+            cmds.entity(invulnerable_id).remove::<Invulnerable>();
+        }
+    }
+}
+
+//Failed tasks always cost health, while hits are ignored during invulnerability frames
+fn apply_damage(
+    mut cmds: Commands,
+    mut damage_evr: EventReader<DamageEvent>,
+    mut health_qry: Query<(&mut PlayerHealthBar, Has<Invulnerable>)>,
+) {
+    let mut hit_ids = Vec::new();
+    for damage in damage_evr.read() {
+        let Ok((mut health, invulnerable)) = health_qry.get_mut(damage.target_id) else {
+            continue;
+        };
+        let was_hit = invulnerable || hit_ids.contains(&damage.target_id);
+        if was_hit && damage.source != DamageSource::TaskFailure {
+            continue;
+        }
+        health.damage(damage.half_hearts);
+        hit_ids.push(damage.target_id);
+
+        let mut target = cmds.entity(damage.target_id);
+        target.insert(Invulnerable::new(HIT_INVULNERABLE_SECS));
+        if damage.knockback != Vec2::ZERO {
+            target.insert(Knockback::new(damage.knockback));
+        }
+    }
+}
+
+pub fn health_plugin(app: &mut App) {
+    app.add_event::<DamageEvent>().add_systems(
+        Update,
+        (expire_invulnerability, apply_damage)
+            .chain()
+            .run_if(in_state(GameState::Playing)),
+    );
+}
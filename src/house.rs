@@ -0,0 +1,155 @@
+use {
+    super::{
+        game_state::GameState,
+        perception::NoiseEvent,
+        task::{Task, TaskPressureEvent},
+        tile::AVG_TILE_DIMENSION,
+        zombie::{Zombie, ZombieAttackEvent},
+    },
+    bevy::prelude::*,
+    rand::Rng,
+};
+
+const HOUSE_GOAL_CHANCE: f64 = 0.4;
+const DOOR_NOISE_RADIUS: f32 = AVG_TILE_DIMENSION * 8.;
+const TASK_PRESSURE_SECS: f32 = 3.;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum HouseTargetKind {
+    Window,
+    Door,
+    Generator,
+}
+
+impl HouseTargetKind {
+    fn integrity(self) -> f32 {
+        match self {
+            Self::Window => 10.,
+            Self::Door => 20.,
+            Self::Generator => 15.,
+        }
+    }
+
+    fn task(self) -> Option<Task> {
+        match self {
+            Self::Window => Some(Task::BoardWindow),
+            Self::Generator => Some(Task::PowerGenerator),
+            Self::Door => None,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct HouseTarget {
+    kind: HouseTargetKind,
+    integrity: f32,
+}
+
+impl HouseTarget {
+    pub fn is_intact(&self) -> bool {
+        self.integrity > 0.
+    }
+}
+
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+pub enum ZombieGoal {
+    Player,
+    House(Entity),
+}
+
+pub fn spawn_house_target(cmds: &mut Commands, pos: Vec3, kind: HouseTargetKind) {
+    cmds.spawn((
+        StateScoped(GameState::Playing),
+        HouseTarget {
+            kind,
+            integrity: kind.integrity(),
+        },
+        TransformBundle::from_transform(Transform::from_translation(pos)),
+    ));
+}
+
+fn closest_house_target(
+    pos: Vec2,
+    house_target_qry: &Query<(Entity, &HouseTarget, &Transform)>,
+) -> Option<Entity> {
+    house_target_qry
+        .iter()
+        .filter(|(_, house_target, _)| house_target.is_intact())
+        .min_by(|(_, _, target_a_xform), (_, _, target_b_xform)| {
+            (target_a_xform.translation.truncate().distance(pos))
+                .total_cmp(&target_b_xform.translation.truncate().distance(pos))
+        })
+        .map(|(house_target_id, _, _)| house_target_id)
+}
+
+#[allow(clippy::type_complexity)]
+fn assign_zombie_goals(
+    mut cmds: Commands,
+    zombie_qry: Query<(Entity, &Transform), (With<Zombie>, Without<ZombieGoal>)>,
+    house_target_qry: Query<(Entity, &HouseTarget, &Transform)>,
+) {
+    let mut thread_rng = rand::thread_rng();
+    for (zombie_id, zombie_xform) in &zombie_qry {
+        let goal = thread_rng
+            .gen_bool(HOUSE_GOAL_CHANCE)
+            .then(|| closest_house_target(zombie_xform.translation.truncate(), &house_target_qry))
+            .flatten()
+            .map_or(ZombieGoal::Player, ZombieGoal::House);
+        cmds.entity(zombie_id).insert(goal);
+    }
+}
+
+fn retarget_zombie_goals(
+    mut goal_qry: Query<&mut ZombieGoal>,
+    house_target_qry: Query<&HouseTarget>,
+) {
+    for mut goal in &mut goal_qry {
+        if let ZombieGoal::House(house_target_id) = *goal {
+            if !house_target_qry
+                .get(house_target_id)
+                .is_ok_and(HouseTarget::is_intact)
+            {
+                *goal = ZombieGoal::Player;
+            }
+        }
+    }
+}
+
+fn damage_house_targets(
+    mut attack_evr: EventReader<ZombieAttackEvent>,
+    mut house_target_qry: Query<(&mut HouseTarget, &Transform)>,
+    mut noise_evw: EventWriter<NoiseEvent>,
+    mut task_pressure_evw: EventWriter<TaskPressureEvent>,
+) {
+    for attack in attack_evr.read() {
+        let Ok((mut house_target, house_target_xform)) = house_target_qry.get_mut(attack.target_id)
+        else {
+            continue;
+        };
+        house_target.integrity -= 1.;
+        if house_target.kind == HouseTargetKind::Door {
+            noise_evw.send(NoiseEvent {
+                pos: house_target_xform.translation.truncate(),
+                radius: DOOR_NOISE_RADIUS,
+            });
+        }
+        if let Some(task) = house_target.kind.task() {
+            task_pressure_evw.send(TaskPressureEvent {
+                task,
+                secs: TASK_PRESSURE_SECS,
+            });
+        }
+    }
+}
+
+pub fn house_plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            assign_zombie_goals,
+            retarget_zombie_goals,
+            damage_house_targets,
+        )
+            .run_if(in_state(GameState::Playing)),
+    );
+}
@@ -0,0 +1,339 @@
+use {
+    super::{
+        combat::{EquippedWeapon, WeaponDef, WeaponKind},
+        game_state::GameState,
+        health::PlayerHealthBar,
+        level::LevelObject,
+        pickup::{Consumable, UsingConsumable},
+        player::{self, Player, PlayerAction},
+        throwable::Throwable,
+    },
+    bevy::prelude::*,
+    leafwing_input_manager::prelude::*,
+};
+
+const STARTING_MAGAZINES: u32 = 2;
+const HOTBAR_ACTIONS: [PlayerAction; Inventory::SIZE] = [
+    PlayerAction::Hotbar1,
+    PlayerAction::Hotbar2,
+    PlayerAction::Hotbar3,
+    PlayerAction::Hotbar4,
+    PlayerAction::Hotbar5,
+    PlayerAction::Hotbar6,
+];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Item {
+    Weapon(&'static WeaponDef),
+    Ammo,
+    Consumable(Consumable),
+    Throwable(Throwable),
+    Planks,
+    FireExtinguisher,
+}
+
+impl Item {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Weapon(weapon) => weapon.name,
+            Self::Ammo => "Ammo",
+            Self::Consumable(consumable) => consumable.name(),
+            Self::Throwable(throwable) => throwable.name(),
+            Self::Planks => "Planks",
+            Self::FireExtinguisher => "Fire extinguisher",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Self::Consumable(consumable) => consumable.color(),
+            _ => Color::WHITE,
+        }
+    }
+
+    //Ammo is counted in rounds, everything else in units
+    fn max_stack(&self) -> u32 {
+        match self {
+            Self::Weapon(_) | Self::FireExtinguisher => 1,
+            Self::Ammo => 60,
+            Self::Consumable(Consumable::Bandage) => 5,
+            Self::Consumable(Consumable::Food) => 3,
+            Self::Consumable(Consumable::Medkit) => 2,
+            Self::Throwable(_) => 3,
+            Self::Planks => 4,
+        }
+    }
+}
+
+impl TryFrom<LevelObject> for Item {
+    type Error = ();
+
+    fn try_from(lvl_obj: LevelObject) -> Result<Self, Self::Error> {
+        match lvl_obj {
+            LevelObject::Bandage => Ok(Self::Consumable(Consumable::Bandage)),
+            LevelObject::FireExtinguisher => Ok(Self::FireExtinguisher),
+            LevelObject::Flashbang => Ok(Self::Throwable(Throwable::Flashbang)),
+            LevelObject::Food => Ok(Self::Consumable(Consumable::Food)),
+            LevelObject::Medkit => Ok(Self::Consumable(Consumable::Medkit)),
+            LevelObject::Molotov => Ok(Self::Throwable(Throwable::Molotov)),
+            LevelObject::NoiseMaker => Ok(Self::Throwable(Throwable::NoiseMaker)),
+            LevelObject::Planks => Ok(Self::Planks),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct ItemStack {
+    pub item: Item,
+    pub count: u32,
+}
+
+//Every slot is on the hotbar, and the selected one decides what the player holds
+#[derive(Component)]
+pub struct Inventory {
+    slots: [Option<ItemStack>; Self::SIZE],
+    selected: usize,
+}
+
+impl Inventory {
+    pub const SIZE: usize = 6;
+
+    pub fn new(weapon: &'static WeaponDef) -> Self {
+        let mut inventory = Self {
+            slots: [None; Self::SIZE],
+            selected: 0,
+        };
+        inventory.add(Item::Weapon(weapon), 1);
+        if let WeaponKind::Firearm { magazine, .. } = weapon.kind {
+            inventory.add(Item::Ammo, magazine * STARTING_MAGAZINES);
+        }
+        inventory
+    }
+
+    pub fn slot(&self, idx: usize) -> Option<ItemStack> {
+        self.slots[idx]
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn selected_item(&self) -> Option<Item> {
+        self.slots[self.selected].map(|stack| stack.item)
+    }
+
+    pub fn count(&self, item: Item) -> u32 {
+        self.slots
+            .iter()
+            .flatten()
+            .filter(|stack| stack.item == item)
+            .map(|stack| stack.count)
+            .sum()
+    }
+
+    pub fn contains(&self, item: Item) -> bool {
+        self.count(item) > 0
+    }
+
+    pub fn has_firearm(&self) -> bool {
+        self.slots.iter().flatten().any(|stack| {
+            matches!(
+                stack.item,
+                Item::Weapon(WeaponDef {
+                    kind: WeaponKind::Firearm { .. },
+                    ..
+                })
+            )
+        })
+    }
+
+    //Tops up existing stacks before filling empty slots, returning how many didn't fit
+    pub fn add(&mut self, item: Item, mut count: u32) -> u32 {
+        for stack in self.slots.iter_mut().flatten() {
+            if stack.item == item {
+                let added = (item.max_stack() - stack.count).min(count);
+                stack.count += added;
+                count -= added;
+            }
+        }
+        for slot in self.slots.iter_mut().filter(|slot| slot.is_none()) {
+            if count == 0 {
+                break;
+            }
+            let added = item.max_stack().min(count);
+            *slot = Some(ItemStack { item, count: added });
+            count -= added;
+        }
+        count
+    }
+
+    //Takes from the last matching stacks first, returning how many were taken
+    pub fn take(&mut self, item: Item, count: u32) -> u32 {
+        let mut taken = 0;
+        for slot in self.slots.iter_mut().rev() {
+            let Some(stack) = slot.as_mut().filter(|stack| stack.item == item) else {
+                continue;
+            };
+            let took = stack.count.min(count - taken);
+            stack.count -= took;
+            taken += took;
+            if stack.count == 0 {
+                *slot = None;
+            }
+            if taken == count {
+                break;
+            }
+        }
+        taken
+    }
+}
+
+fn select_hotbar_slot(mut player_qry: Query<(&mut Inventory, &ActionState<PlayerAction>)>) {
+    let Ok((mut inventory, player_in)) = player_qry.get_single_mut() else {
+        return;
+    };
+    let selected = if let Some(idx) = HOTBAR_ACTIONS
+        .iter()
+        .position(|action| player_in.just_pressed(action))
+    {
+        idx
+    } else if player_in.just_pressed(&PlayerAction::NextItem) {
+        (inventory.selected + 1) % Inventory::SIZE
+    } else if player_in.just_pressed(&PlayerAction::PreviousItem) {
+        (inventory.selected + Inventory::SIZE - 1) % Inventory::SIZE
+    } else {
+        return;
+    };
+    if inventory.selected != selected {
+        inventory.selected = selected;
+    }
+}
+
+//Holstering a firearm unloads it back into the ammo stacks, and drawing one loads it straight away
+#[allow(clippy::type_complexity)]
+fn equip_selected_weapon(
+    mut cmds: Commands,
+    mut player_qry: Query<
+        (Entity, &mut Inventory, Option<&mut EquippedWeapon>),
+        (With<Player>, Changed<Inventory>),
+    >,
+) {
+    let Ok((player_id, mut inventory, weapon)) = player_qry.get_single_mut() else {
+        return;
+    };
+    let selected_weapon = match inventory.selected_item() {
+        Some(Item::Weapon(weapon_def)) => Some(weapon_def),
+        _ => None,
+    };
+    if weapon.as_ref().map(|weapon| weapon.def()) == selected_weapon {
+        return;
+    }
+    if let Some(mut weapon) = weapon {
+        let rounds = weapon.unload();
+        inventory.add(Item::Ammo, rounds);
+        cmds.entity(player_id).remove::<EquippedWeapon>();
+    }
+    if let Some(weapon_def) = selected_weapon {
+        let mut weapon = EquippedWeapon::unloaded(weapon_def);
+        let rounds = inventory.take(Item::Ammo, weapon.magazine_space());
+        weapon.load(rounds);
+        cmds.entity(player_id).insert(weapon);
+    }
+}
+
+//Firing with a consumable selected uses one from the stack instead
+fn use_selected_consumable(
+    mut cmds: Commands,
+    mut player_qry: Query<(
+        Entity,
+        &Player,
+        &mut Inventory,
+        &PlayerHealthBar,
+        &ActionState<PlayerAction>,
+    )>,
+) {
+    let Ok((player_id, player, mut inventory, player_hp_bar, player_in)) =
+        player_qry.get_single_mut()
+    else {
+        return;
+    };
+    let Some(Item::Consumable(consumable)) = inventory.selected_item() else {
+        return;
+    };
+    if player.doing_task()
+        || player_hp_bar.is_full()
+        || !player_in.just_pressed(&PlayerAction::Fire)
+    {
+        return;
+    }
+    inventory.take(Item::Consumable(consumable), 1);
+    cmds.entity(player_id)
+        .insert(UsingConsumable::new(consumable));
+}
+
+pub fn inventory_plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            select_hotbar_slot,
+            equip_selected_weapon,
+            use_selected_consumable.after(player::player_task_input),
+        )
+            .chain()
+            .run_if(in_state(GameState::Playing))
+            .run_if(player::can_act),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::combat::{BAT, PISTOL},
+    };
+
+    const BANDAGE: Item = Item::Consumable(Consumable::Bandage);
+
+    fn stacks(inventory: &Inventory) -> Vec<(Item, u32)> {
+        (0..Inventory::SIZE)
+            .filter_map(|idx| inventory.slot(idx))
+            .map(|stack| (stack.item, stack.count))
+            .collect()
+    }
+
+    #[test]
+    fn starts_with_the_weapon_and_ammo_for_firearms() {
+        let pistol_stacks = stacks(&Inventory::new(&PISTOL));
+        assert!(pistol_stacks == [(Item::Weapon(&PISTOL), 1), (Item::Ammo, 24)]);
+        assert!(stacks(&Inventory::new(&BAT)) == [(Item::Weapon(&BAT), 1)]);
+    }
+
+    #[test]
+    fn tops_up_stacks_before_filling_empty_slots() {
+        let mut inventory = Inventory::new(&BAT);
+        assert_eq!(inventory.add(BANDAGE, 3), 0);
+        assert_eq!(inventory.add(BANDAGE, 4), 0);
+        assert!(stacks(&inventory) == [(Item::Weapon(&BAT), 1), (BANDAGE, 5), (BANDAGE, 2)]);
+        assert_eq!(inventory.count(BANDAGE), 7);
+    }
+
+    #[test]
+    fn returns_what_does_not_fit() {
+        let mut inventory = Inventory::new(&BAT);
+        assert_eq!(inventory.add(Item::Planks, 30), 10);
+        assert_eq!(inventory.count(Item::Planks), 20);
+        assert_eq!(inventory.add(Item::Weapon(&PISTOL), 1), 1);
+        assert!(!inventory.has_firearm());
+    }
+
+    #[test]
+    fn takes_from_the_last_stacks_first() {
+        let mut inventory = Inventory::new(&BAT);
+        inventory.add(BANDAGE, 7);
+        assert_eq!(inventory.take(BANDAGE, 3), 3);
+        assert!(stacks(&inventory) == [(Item::Weapon(&BAT), 1), (BANDAGE, 4)]);
+        assert_eq!(inventory.take(BANDAGE, 10), 4);
+        assert!(!inventory.contains(BANDAGE));
+    }
+}
@@ -1,6 +1,10 @@
 use {
     super::{
         asset_owner::TextureAtlasOwner,
+        character::SelectedCharacter,
+        controls::Controls,
+        house::{self, HouseTargetKind},
+        pickup,
         player::{self, Player},
         tile::{self, Tile, TILE_SIZE},
         zombie::ZombieSpawns,
@@ -10,11 +14,12 @@ use {
     rand::Rng,
 };
 
-pub const LEVEL_SIZE: Vec3 = Vec3::new(22., 27., 2.);
+pub const LEVEL_SIZE: Vec3 = Vec3::new(22., 27., 3.);
 
 #[repr(usize)]
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub enum LevelObject {
+    Bandage,
     BedsideTable,
     BigTvLeft,
     BigTvRight,
@@ -34,6 +39,7 @@ pub enum LevelObject {
     Countertop,
     Debris,
     Dirt,
+    Door,
     DoubleBedLeftBody,
     DoubleBedLeftFootboard,
     DoubleBedLeftHeadboard,
@@ -41,13 +47,22 @@ pub enum LevelObject {
     DoubleBedRightFootboard,
     DoubleBedRightHeadboard,
     FallenLeaves,
+    Fire,
+    FireExtinguisher,
+    Flashbang,
     Floor,
+    Food,
     Grass,
+    Generator,
     Gravel,
     LeftWall,
     Marble,
+    Medkit,
+    Molotov,
+    NoiseMaker,
     Nothing,
     OilSpill,
+    Planks,
     Player,
     Recliner,
     RightWall,
@@ -69,6 +84,7 @@ pub enum LevelObject {
     TreeTopRight,
     UShapedWall,
     Water,
+    Window,
     WoodPlank,
     Zombie,
 }
@@ -76,6 +92,7 @@ pub enum LevelObject {
 impl LevelObject {
     pub fn id(self) -> usize {
         match self {
+            Self::Bandage | Self::Medkit => 132,
             Self::BedsideTable => 478,
             Self::BigTvLeft => 532,
             Self::BigTvRight => 531,
@@ -116,11 +133,17 @@ impl LevelObject {
             Self::DoubleBedRightFootboard => 48,
             Self::DoubleBedRightHeadboard => 102,
             Self::FallenLeaves => 212,
+            Self::Fire => 318,
+            Self::FireExtinguisher => 315,
+            Self::Flashbang => 316,
             Self::Floor => 96,
+            Self::Food => 131,
             Self::Grass => rand::thread_rng().gen_range(0..=3),
             Self::Gravel => rand::thread_rng().gen_range(6..=9),
             Self::LeftWall => 175,
             Self::Marble => 11,
+            Self::Molotov => 317,
+            Self::NoiseMaker => 186,
             Self::OilSpill => 319,
             Self::Recliner => 504,
             Self::RightWall => 176,
@@ -149,8 +172,13 @@ impl LevelObject {
             Self::TreeTopRight => 181,
             Self::UShapedWall => 123,
             Self::Water => rand::thread_rng().gen_range(18..=19),
-            Self::WoodPlank => 264,
-            Self::Nothing | Self::Player | Self::Zombie => usize::MAX,
+            Self::Planks | Self::WoodPlank => 264,
+            Self::Door
+            | Self::Generator
+            | Self::Nothing
+            | Self::Player
+            | Self::Window
+            | Self::Zombie => usize::MAX,
         }
     }
 }
@@ -693,7 +721,7 @@ pub const LEVEL_LAYOUT: [[[LevelObject; LEVEL_SIZE.x as usize]; LEVEL_SIZE.y as
             LevelObject::Nothing,
             LevelObject::Nothing,
             LevelObject::Nothing,
-            LevelObject::Nothing,
+            LevelObject::Box,
             LevelObject::Nothing,
             LevelObject::Zombie,
         ],
@@ -732,7 +760,7 @@ pub const LEVEL_LAYOUT: [[[LevelObject; LEVEL_SIZE.x as usize]; LEVEL_SIZE.y as
             LevelObject::Nothing,
             LevelObject::LeftWall,
             LevelObject::Nothing,
-            LevelObject::Nothing,
+            LevelObject::Box,
             LevelObject::Nothing,
             LevelObject::Nothing,
             LevelObject::RightWall,
@@ -757,7 +785,7 @@ pub const LEVEL_LAYOUT: [[[LevelObject; LEVEL_SIZE.x as usize]; LEVEL_SIZE.y as
             LevelObject::LeftWall,
             LevelObject::Nothing,
             LevelObject::Nothing,
-            LevelObject::Nothing,
+            LevelObject::FireExtinguisher,
             LevelObject::Nothing,
             LevelObject::RightWall,
             LevelObject::Nothing,
@@ -823,7 +851,7 @@ pub const LEVEL_LAYOUT: [[[LevelObject; LEVEL_SIZE.x as usize]; LEVEL_SIZE.y as
             LevelObject::Nothing,
             LevelObject::Nothing,
             LevelObject::LeftWall,
-            LevelObject::Nothing,
+            LevelObject::Box,
             LevelObject::Nothing,
             LevelObject::Nothing,
             LevelObject::LeftWall,
@@ -832,8 +860,8 @@ pub const LEVEL_LAYOUT: [[[LevelObject; LEVEL_SIZE.x as usize]; LEVEL_SIZE.y as
             LevelObject::Nothing,
             LevelObject::Nothing,
             LevelObject::Nothing,
-            LevelObject::Nothing,
-            LevelObject::Nothing,
+            LevelObject::Box,
+            LevelObject::Planks,
             LevelObject::Nothing,
             LevelObject::RightWall,
             LevelObject::Nothing,
@@ -852,7 +880,7 @@ pub const LEVEL_LAYOUT: [[[LevelObject; LEVEL_SIZE.x as usize]; LEVEL_SIZE.y as
             LevelObject::BottomWall,
             LevelObject::LeftWall,
             LevelObject::Nothing,
-            LevelObject::Nothing,
+            LevelObject::Food,
             LevelObject::Nothing,
             LevelObject::Nothing,
             LevelObject::Nothing,
@@ -895,7 +923,7 @@ pub const LEVEL_LAYOUT: [[[LevelObject; LEVEL_SIZE.x as usize]; LEVEL_SIZE.y as
             LevelObject::Nothing,
             LevelObject::Nothing,
             LevelObject::LeftWall,
-            LevelObject::Nothing,
+            LevelObject::Bandage,
             LevelObject::Nothing,
             LevelObject::Nothing,
             LevelObject::BottomLeftWall,
@@ -991,7 +1019,7 @@ pub const LEVEL_LAYOUT: [[[LevelObject; LEVEL_SIZE.x as usize]; LEVEL_SIZE.y as
             LevelObject::Nothing,
             LevelObject::Nothing,
             LevelObject::BottomLeftWall,
-            LevelObject::Nothing,
+            LevelObject::Planks,
             LevelObject::RightWall,
             LevelObject::Nothing,
             LevelObject::LeftWall,
@@ -1074,7 +1102,7 @@ pub const LEVEL_LAYOUT: [[[LevelObject; LEVEL_SIZE.x as usize]; LEVEL_SIZE.y as
             LevelObject::Nothing,
             LevelObject::Nothing,
             LevelObject::Nothing,
-            LevelObject::Nothing,
+            LevelObject::Medkit,
             LevelObject::RightWall,
             LevelObject::Nothing,
             LevelObject::Nothing,
@@ -1095,7 +1123,7 @@ pub const LEVEL_LAYOUT: [[[LevelObject; LEVEL_SIZE.x as usize]; LEVEL_SIZE.y as
             LevelObject::Gravel,
             LevelObject::RightWall,
             LevelObject::Nothing,
-            LevelObject::Nothing,
+            LevelObject::Box,
             LevelObject::Nothing,
             LevelObject::Nothing,
             LevelObject::Nothing,
@@ -1227,6 +1255,150 @@ pub const LEVEL_LAYOUT: [[[LevelObject; LEVEL_SIZE.x as usize]; LEVEL_SIZE.y as
         ],
         [LevelObject::Zombie; LEVEL_SIZE.x as usize],
     ],
+    [
+        [LevelObject::Nothing; LEVEL_SIZE.x as usize],
+        [LevelObject::Nothing; LEVEL_SIZE.x as usize],
+        [LevelObject::Nothing; LEVEL_SIZE.x as usize],
+        [LevelObject::Nothing; LEVEL_SIZE.x as usize],
+        [
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Window,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+        ],
+        [LevelObject::Nothing; LEVEL_SIZE.x as usize],
+        [LevelObject::Nothing; LEVEL_SIZE.x as usize],
+        [
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Window,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+        ],
+        [LevelObject::Nothing; LEVEL_SIZE.x as usize],
+        [LevelObject::Nothing; LEVEL_SIZE.x as usize],
+        [LevelObject::Nothing; LEVEL_SIZE.x as usize],
+        [LevelObject::Nothing; LEVEL_SIZE.x as usize],
+        [
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Window,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Window,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+        ],
+        [LevelObject::Nothing; LEVEL_SIZE.x as usize],
+        [LevelObject::Nothing; LEVEL_SIZE.x as usize],
+        [
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Generator,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+        ],
+        [LevelObject::Nothing; LEVEL_SIZE.x as usize],
+        [LevelObject::Nothing; LEVEL_SIZE.x as usize],
+        [LevelObject::Nothing; LEVEL_SIZE.x as usize],
+        [LevelObject::Nothing; LEVEL_SIZE.x as usize],
+        [LevelObject::Nothing; LEVEL_SIZE.x as usize],
+        [LevelObject::Nothing; LEVEL_SIZE.x as usize],
+        [
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Door,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+            LevelObject::Nothing,
+        ],
+        [LevelObject::Nothing; LEVEL_SIZE.x as usize],
+        [LevelObject::Nothing; LEVEL_SIZE.x as usize],
+        [LevelObject::Nothing; LEVEL_SIZE.x as usize],
+        [LevelObject::Nothing; LEVEL_SIZE.x as usize],
+    ],
 ];
 
 pub fn idx_to_world_pos(x: usize, y: usize, z: usize) -> Vec3 {
@@ -1238,12 +1410,22 @@ pub fn idx_to_world_pos(x: usize, y: usize, z: usize) -> Vec3 {
     .translation
 }
 
+pub fn world_pos_to_idx(pos: Vec2) -> Option<(usize, usize)> {
+    let level_size = LEVEL_SIZE.truncate();
+    let idx = (pos / TILE_SIZE + (level_size - 1.) / 2.).round();
+    let (x, y) = (idx.x, level_size.y - idx.y - 1.);
+    (x >= 0. && x < level_size.x && y >= 0. && y < level_size.y).then_some((x as usize, y as usize))
+}
+
 pub fn spawn_level_objects(
     mut cmds: Commands,
     tile_tex_atlas: Res<TextureAtlasOwner<Tile>>,
     player_tex_atlas: Res<TextureAtlasOwner<Player>>,
+    selected_character: Res<SelectedCharacter>,
+    controls: Res<Controls>,
     mut zombie_spawns: ResMut<ZombieSpawns>,
 ) {
+    zombie_spawns.0.clear();
     for z in 0..LEVEL_SIZE.z as usize {
         for y in 0..LEVEL_SIZE.y as usize {
             for x in 0..LEVEL_SIZE.x as usize {
@@ -1251,10 +1433,34 @@ pub fn spawn_level_objects(
                 let pos = idx_to_world_pos(x, y, z);
                 match lvl_obj {
                     LevelObject::Nothing => (),
-                    LevelObject::Player => {
-                        player::spawn_player(&mut cmds, pos.truncate(), &player_tex_atlas)
-                    }
+                    LevelObject::Player => player::spawn_player(
+                        &mut cmds,
+                        pos.truncate(),
+                        &player_tex_atlas,
+                        selected_character.0,
+                        &controls,
+                    ),
                     LevelObject::Zombie => zombie_spawns.0.push(pos.truncate()),
+                    LevelObject::Door => {
+                        house::spawn_house_target(&mut cmds, pos, HouseTargetKind::Door)
+                    }
+                    LevelObject::Generator => {
+                        house::spawn_house_target(&mut cmds, pos, HouseTargetKind::Generator)
+                    }
+                    LevelObject::Window => {
+                        house::spawn_house_target(&mut cmds, pos, HouseTargetKind::Window)
+                    }
+                    LevelObject::Box => pickup::spawn_container(&mut cmds, pos, &tile_tex_atlas),
+                    LevelObject::Bandage
+                    | LevelObject::FireExtinguisher
+                    | LevelObject::Flashbang
+                    | LevelObject::Food
+                    | LevelObject::Medkit
+                    | LevelObject::Molotov
+                    | LevelObject::NoiseMaker
+                    | LevelObject::Planks => {
+                        pickup::spawn_pickup(&mut cmds, pos, lvl_obj, &tile_tex_atlas)
+                    }
                     _ => tile::spawn_tile(
                         &mut cmds,
                         pos.truncate(),
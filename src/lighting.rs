@@ -0,0 +1,231 @@
+use {
+    super::{
+        game_state::GameState,
+        player::Player,
+        task::{Task, TaskCompletedEvent},
+        tile::{Wall, AVG_TILE_DIMENSION, TILE_SIZE},
+        zombie::Zombie,
+    },
+    bevy::{
+        prelude::*,
+        render::{
+            mesh::{Indices, PrimitiveTopology, VertexAttributeValues},
+            render_asset::RenderAssetUsages,
+            view::NoFrustumCulling,
+        },
+        sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+    },
+    bevy_rapier2d::prelude::*,
+    std::f32::consts::TAU,
+};
+
+const LIGHT_RAYS: usize = 240;
+const FLASHLIGHT_RANGE: f32 = AVG_TILE_DIMENSION * 9.;
+const FLASHLIGHT_HALF_ANGLE: f32 = 0.45;
+const FLASHLIGHT_SOFT_EDGE: f32 = 0.15;
+const AMBIENT_RADIUS: f32 = AVG_TILE_DIMENSION * 2.5;
+const FALLOFF_START: f32 = 0.6;
+const WALL_FADE: f32 = AVG_TILE_DIMENSION * 0.3;
+const DARKNESS_REACH: f32 = TILE_SIZE.x * 64.;
+const DARKNESS_Z: f32 = 20.;
+const DARKNESS: f32 = 0.92;
+const POWERED_DARKNESS: f32 = 0.35;
+const DARKNESS_CHANGE_PER_SEC: f32 = 1.5;
+const GENERATOR_POWER_SECS: f32 = 60.;
+
+//Counts down while the generator keeps the house lights on
+#[derive(Resource, Default)]
+struct HouseLights {
+    powered_secs: f32,
+}
+
+impl HouseLights {
+    fn is_powered(&self) -> bool {
+        self.powered_secs > 0.
+    }
+}
+
+//A ring of quads from the edge of the lit area out past the screen, so everything the light
+//doesn't reach is covered. Each ray keeps how far its light got for checking what's lit, and the
+//position and facing it was cast from
+#[derive(Component)]
+struct Darkness {
+    alpha: f32,
+    lit_dists: Vec<f32>,
+    lit_from: Option<(Vec2, Vec2)>,
+}
+
+impl Darkness {
+    fn ray_dir(idx: usize) -> Vec2 {
+        Vec2::from_angle(idx as f32 / LIGHT_RAYS as f32 * TAU)
+    }
+
+    fn is_lit(&self, light_pos: Vec2, pos: Vec2) -> bool {
+        let offset = pos - light_pos;
+        let angle = offset.y.atan2(offset.x).rem_euclid(TAU);
+        let idx = (angle / TAU * LIGHT_RAYS as f32).round() as usize % LIGHT_RAYS;
+        offset.length() <= self.lit_dists[idx]
+    }
+}
+
+//The flashlight reaches furthest inside its cone, tapering down to the ambient glow at the edges
+fn light_range(facing: Vec2, ray_dir: Vec2) -> f32 {
+    let off_angle = facing.angle_between(ray_dir).abs();
+    let t = ((off_angle - FLASHLIGHT_HALF_ANGLE) / FLASHLIGHT_SOFT_EDGE).clamp(0., 1.);
+    FLASHLIGHT_RANGE + (AMBIENT_RADIUS - FLASHLIGHT_RANGE) * t
+}
+
+fn spawn_darkness(
+    mut cmds: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    //Three vertices per ray, where the darkness starts, where it's full and far off screen
+    let indices = (0..LIGHT_RAYS)
+        .flat_map(|idx| {
+            let (ray, next_ray) = (idx as u32 * 3, (idx + 1) as u32 % LIGHT_RAYS as u32 * 3);
+            (0..2).flat_map(move |band| {
+                let (inner, outer) = (ray + band, ray + band + 1);
+                let (next_inner, next_outer) = (next_ray + band, next_ray + band + 1);
+                [inner, outer, next_inner, outer, next_outer, next_inner]
+            })
+        })
+        .collect::<Vec<_>>();
+    let mesh = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, vec![[0.; 3]; LIGHT_RAYS * 3])
+    .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, vec![[0.; 4]; LIGHT_RAYS * 3])
+    .with_inserted_indices(Indices::U32(indices));
+    cmds.spawn((
+        Darkness {
+            alpha: DARKNESS,
+            lit_dists: vec![0.; LIGHT_RAYS],
+            lit_from: None,
+        },
+        StateScoped(GameState::Playing),
+        MaterialMesh2dBundle {
+            mesh: Mesh2dHandle(meshes.add(mesh)),
+            material: materials.add(Color::WHITE),
+            transform: Transform::from_xyz(0., 0., DARKNESS_Z),
+            ..default()
+        },
+        NoFrustumCulling,
+    ));
+    cmds.insert_resource(HouseLights::default());
+}
+
+fn power_house_lights(
+    mut house_lights: ResMut<HouseLights>,
+    mut task_completed_evr: EventReader<TaskCompletedEvent>,
+    time: Res<Time>,
+) {
+    house_lights.powered_secs = (house_lights.powered_secs - time.delta_seconds()).max(0.);
+    if task_completed_evr
+        .read()
+        .any(|completed| completed.task == Task::PowerGenerator)
+    {
+        house_lights.powered_secs = GENERATOR_POWER_SECS;
+    }
+}
+
+//Walls stop each ray short, while open rays fade out over the last part of their range
+fn cast_light(
+    mut darkness_qry: Query<(&mut Darkness, &mut Transform, &Mesh2dHandle)>,
+    player_qry: Query<&Transform, (With<Player>, Without<Darkness>)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    house_lights: Res<HouseLights>,
+    wall_qry: Query<(), With<Wall>>,
+    rapier_ctx: Res<RapierContext>,
+    time: Res<Time>,
+) {
+    let Ok((mut darkness, mut darkness_xform, darkness_mesh)) = darkness_qry.get_single_mut()
+    else {
+        return;
+    };
+    let Ok(player_xform) = player_qry.get_single() else {
+        return;
+    };
+    let target_alpha = if house_lights.is_powered() {
+        POWERED_DARKNESS
+    } else {
+        DARKNESS
+    };
+    let max_change = DARKNESS_CHANGE_PER_SEC * time.delta_seconds();
+    let alpha = darkness.alpha + (target_alpha - darkness.alpha).clamp(-max_change, max_change);
+    let light_pos = player_xform.translation.truncate();
+    let facing = (player_xform.rotation * Vec3::X).truncate();
+
+    //Walls never move, so the mesh only changes when the player or the darkness does
+    if darkness.lit_from == Some((light_pos, facing)) && darkness.alpha == alpha {
+        return;
+    }
+    let Some(mesh) = meshes.get_mut(&darkness_mesh.0) else {
+        return;
+    };
+    darkness.lit_from = Some((light_pos, facing));
+    darkness.alpha = alpha;
+
+    let is_wall = |collider_id| wall_qry.contains(collider_id);
+    let wall_filter = QueryFilter::new().predicate(&is_wall);
+    if let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute_mut(Mesh::ATTRIBUTE_POSITION)
+    {
+        for (idx, ray_positions) in positions.chunks_exact_mut(3).enumerate() {
+            let ray_dir = Darkness::ray_dir(idx);
+            let range = light_range(facing, ray_dir);
+            let (fade_start, lit_dist) =
+                match rapier_ctx.cast_ray(light_pos, ray_dir, range, true, wall_filter) {
+                    Some((_, wall_dist)) => (wall_dist, wall_dist + WALL_FADE),
+                    None => (range * FALLOFF_START, range),
+                };
+            darkness.lit_dists[idx] = lit_dist;
+            let dists = [fade_start, lit_dist, DARKNESS_REACH];
+            for (position, dist) in ray_positions.iter_mut().zip(dists) {
+                *position = (ray_dir * dist).extend(0.).to_array();
+            }
+        }
+    }
+    if let Some(VertexAttributeValues::Float32x4(colors)) =
+        mesh.attribute_mut(Mesh::ATTRIBUTE_COLOR)
+    {
+        for ray_colors in colors.chunks_exact_mut(3) {
+            ray_colors[1][3] = alpha;
+            ray_colors[2][3] = alpha;
+        }
+    }
+    darkness_xform.translation = light_pos.extend(DARKNESS_Z);
+}
+
+//Zombies out of the light are hidden until the house lights are on
+fn hide_unlit_zombies(
+    mut zombie_qry: Query<(&Transform, &mut Visibility), With<Zombie>>,
+    darkness_qry: Query<(&Darkness, &Transform), Without<Zombie>>,
+    house_lights: Res<HouseLights>,
+) {
+    let Ok((darkness, darkness_xform)) = darkness_qry.get_single() else {
+        return;
+    };
+    let light_pos = darkness_xform.translation.truncate();
+    for (zombie_xform, mut zombie_visibility) in &mut zombie_qry {
+        let lit = house_lights.is_powered()
+            || darkness.is_lit(light_pos, zombie_xform.translation.truncate());
+        let visibility = if lit {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        zombie_visibility.set_if_neq(visibility);
+    }
+}
+
+pub fn lighting_plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::Playing), spawn_darkness)
+        .add_systems(
+            Update,
+            (power_house_lights, cast_light, hide_unlit_zombies)
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        );
+}
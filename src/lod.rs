@@ -0,0 +1,248 @@
+use {
+    super::{
+        boss::Boss,
+        combat::Knockback,
+        game_state::GameState,
+        level::{self, LEVEL_LAYOUT, LEVEL_SIZE},
+        movement::Movement,
+        primary_camera::PrimaryCamera,
+        steering::{Steering, SteeringSet},
+        telegraph::{self, Emerging},
+        throwable::Stunned,
+        tile::{self, AVG_TILE_DIMENSION, BOTTOM_WALL, LEFT_WALL, RIGHT_WALL, TOP_WALL},
+        zombie::{Zombie, ZOMBIE_RADIUS},
+    },
+    bevy::{
+        diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic},
+        prelude::*,
+    },
+    bevy_rapier2d::prelude::*,
+};
+
+const FULL_LOD_MARGIN: f32 = AVG_TILE_DIMENSION * 3.;
+const CHEAP_TICK_INTERVAL: u32 = 4;
+const FULL_LOD_ZOMBIES: DiagnosticPath = DiagnosticPath::const_new("zombies/full_lod");
+const CHEAP_LOD_ZOMBIES: DiagnosticPath = DiagnosticPath::const_new("zombies/cheap_lod");
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ZombieLod {
+    #[default]
+    Full,
+    Cheap,
+}
+
+//Turning the cheap path off puts every zombie through the character controller, so frame times
+//logged by the diagnostics feature can be compared with and without it
+#[derive(Resource)]
+struct CheapLod(bool);
+
+#[allow(clippy::type_complexity)]
+fn update_zombie_lod(
+    mut cmds: Commands,
+    mut zombie_qry: Query<(Entity, &Transform, &mut ZombieLod), (Without<Emerging>, Without<Boss>)>,
+    primary_cam_qry: Query<(&OrthographicProjection, &Transform), With<PrimaryCamera>>,
+    cheap_lod: Res<CheapLod>,
+    mut diagnostics: Diagnostics,
+) {
+    let full_lod_area = telegraph::visible_area(&primary_cam_qry).inflate(FULL_LOD_MARGIN);
+    let (mut full_lod_count, mut cheap_lod_count) = (0, 0);
+    for (zombie_id, zombie_xform, mut zombie_lod) in &mut zombie_qry {
+        let lod = if !cheap_lod.0 || full_lod_area.contains(zombie_xform.translation.truncate()) {
+            ZombieLod::Full
+        } else {
+            ZombieLod::Cheap
+        };
+        match lod {
+            ZombieLod::Full => full_lod_count += 1,
+            ZombieLod::Cheap => cheap_lod_count += 1,
+        }
+        if lod == *zombie_lod {
+            continue;
+        }
+        *zombie_lod = lod;
+        match lod {
+            ZombieLod::Full => {
+                cmds.entity(zombie_id)
+                    .remove::<ColliderDisabled>()
+                    .insert(KinematicCharacterController::default());
+            }
+            ZombieLod::Cheap => {
+                cmds.entity(zombie_id)
+                    .remove::<KinematicCharacterController>()
+                    .insert(ColliderDisabled);
+            }
+        }
+    }
+    diagnostics.add_measurement(&FULL_LOD_ZOMBIES, || full_lod_count as f64);
+    diagnostics.add_measurement(&CHEAP_LOD_ZOMBIES, || cheap_lod_count as f64);
+}
+
+//Wall sides of every level cell, merged across layers, so off-screen zombies can be kept out of
+//walls by a lookup instead of a physics query
+#[derive(Resource)]
+struct WallGrid([[u8; LEVEL_SIZE.x as usize]; LEVEL_SIZE.y as usize]);
+
+impl Default for WallGrid {
+    fn default() -> Self {
+        let mut cells = [[0; LEVEL_SIZE.x as usize]; LEVEL_SIZE.y as usize];
+        for layer in &LEVEL_LAYOUT {
+            for (y, row) in layer.iter().enumerate() {
+                for (x, &lvl_obj) in row.iter().enumerate() {
+                    cells[y][x] |= tile::wall_sides(lvl_obj);
+                }
+            }
+        }
+        Self(cells)
+    }
+}
+
+impl WallGrid {
+    fn sides(&self, idx: Option<(usize, usize)>) -> u8 {
+        idx.map_or(0, |(x, y)| self.0[y][x])
+    }
+
+    fn is_open(&self, pos: Vec2, step: Vec2) -> bool {
+        let x_step = Vec2::new(step.x, 0.);
+        let y_step = Vec2::new(0., step.y);
+        !self.blocks(pos, x_step, RIGHT_WALL, LEFT_WALL)
+            && !self.blocks(pos + x_step, y_step, TOP_WALL, BOTTOM_WALL)
+    }
+
+    //An axis-aligned step is blocked when the zombie's leading edge crosses into the next cell
+    //through a wall on either side of their shared edge
+    fn blocks(&self, pos: Vec2, step: Vec2, forward_wall: u8, backward_wall: u8) -> bool {
+        if step == Vec2::ZERO {
+            return false;
+        }
+        let (exit_wall, entry_wall) = if step.x + step.y > 0. {
+            (forward_wall, backward_wall)
+        } else {
+            (backward_wall, forward_wall)
+        };
+        let lead = pos + step.normalize() * ZOMBIE_RADIUS;
+        let (from, to) = (
+            level::world_pos_to_idx(lead),
+            level::world_pos_to_idx(lead + step),
+        );
+        from != to && (self.sides(from) & exit_wall != 0 || self.sides(to) & entry_wall != 0)
+    }
+}
+
+//Off-screen zombies step along the tile grid's eight directions every few ticks, skipping physics
+//but for the wall grid, sliding along whichever axis is still open
+#[allow(clippy::type_complexity)]
+fn cheap_zombie_movement(
+    mut zombie_qry: Query<
+        (&mut Transform, &Movement, &Steering, &ZombieLod),
+        (With<Zombie>, Without<Knockback>, Without<Stunned>),
+    >,
+    wall_grid: Res<WallGrid>,
+    mut tick: Local<u32>,
+    time: Res<Time>,
+) {
+    *tick = (*tick + 1) % CHEAP_TICK_INTERVAL;
+    if *tick != 0 {
+        return;
+    }
+    let dt = time.delta_seconds() * CHEAP_TICK_INTERVAL as f32;
+    for (mut zombie_xform, zombie_movement, zombie_steering, zombie_lod) in &mut zombie_qry {
+        if *zombie_lod != ZombieLod::Cheap || zombie_steering.heading() == Vec2::ZERO {
+            continue;
+        }
+        let zombie_pos = zombie_xform.translation.truncate();
+        let grid_dir = zombie_steering.heading().round().normalize_or_zero();
+        let step = grid_dir * zombie_movement.max_speed() * dt;
+        let open_step = [step, Vec2::new(step.x, 0.), Vec2::new(0., step.y)]
+            .into_iter()
+            .filter(|&step| step != Vec2::ZERO)
+            .find(|&step| wall_grid.is_open(zombie_pos, step));
+        if let Some(step) = open_step {
+            zombie_xform.translation += step.extend(0.);
+        }
+    }
+}
+
+#[cfg(feature = "diagnostics")]
+fn toggle_cheap_lod(keys: Res<ButtonInput<KeyCode>>, mut cheap_lod: ResMut<CheapLod>) {
+    if keys.just_pressed(KeyCode::F2) {
+        cheap_lod.0 = !cheap_lod.0;
+        info!(
+            "cheap zombie LOD {}",
+            if cheap_lod.0 { "on" } else { "off" }
+        );
+    }
+}
+
+pub fn lod_plugin(app: &mut App) {
+    app.insert_resource(CheapLod(true))
+        .init_resource::<WallGrid>()
+        .register_diagnostic(Diagnostic::new(FULL_LOD_ZOMBIES))
+        .register_diagnostic(Diagnostic::new(CHEAP_LOD_ZOMBIES))
+        .add_systems(
+            FixedUpdate,
+            (
+                update_zombie_lod.before(SteeringSet),
+                cheap_zombie_movement.after(SteeringSet),
+            )
+                .run_if(in_state(GameState::Playing)),
+        );
+
+    #[cfg(feature = "diagnostics")]
+    app.add_plugins((
+        bevy::diagnostic::FrameTimeDiagnosticsPlugin,
+        bevy::diagnostic::LogDiagnosticsPlugin::default(),
+    ))
+    .add_systems(Update, toggle_cheap_lod);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //Step size of a zombie at 100 px/s over one cheap tick of a 64 Hz fixed update
+    const STEP: f32 = 100. * CHEAP_TICK_INTERVAL as f32 / 64.;
+
+    fn cell_with(wall_grid: &WallGrid, wall: u8) -> (usize, usize) {
+        (0..LEVEL_SIZE.y as usize)
+            .flat_map(|y| (0..LEVEL_SIZE.x as usize).map(move |x| (x, y)))
+            .find(|&(x, y)| wall_grid.0[y][x] == wall)
+            .expect("level has a tile with only this wall")
+    }
+
+    #[test]
+    fn world_pos_maps_back_to_its_cell() {
+        for (x, y) in [(0, 0), (21, 26), (10, 4), (18, 15)] {
+            let pos = level::idx_to_world_pos(x, y, 0).truncate();
+            assert_eq!(level::world_pos_to_idx(pos), Some((x, y)));
+            assert_eq!(level::world_pos_to_idx(pos + 31.), Some((x, y)));
+        }
+        assert_eq!(level::world_pos_to_idx(Vec2::splat(10_000.)), None);
+    }
+
+    #[test]
+    fn walls_block_steps_through_their_edge() {
+        let wall_grid = WallGrid::default();
+        let (x, y) = cell_with(&wall_grid, LEFT_WALL);
+        let left_edge = level::idx_to_world_pos(x, y, 0).truncate() - Vec2::X * 32.;
+
+        let inside = left_edge + Vec2::X * (ZOMBIE_RADIUS + STEP / 2.);
+        assert!(!wall_grid.is_open(inside, Vec2::NEG_X * STEP));
+        assert!(wall_grid.is_open(inside, Vec2::X * STEP));
+
+        let outside = left_edge - Vec2::X * (ZOMBIE_RADIUS + STEP / 2.);
+        assert!(!wall_grid.is_open(outside, Vec2::X * STEP));
+        assert!(wall_grid.is_open(outside, Vec2::NEG_X * STEP));
+    }
+
+    #[test]
+    fn diagonal_steps_into_a_wall_are_blocked_but_can_slide() {
+        let wall_grid = WallGrid::default();
+        let (x, y) = cell_with(&wall_grid, TOP_WALL);
+        let top_edge = level::idx_to_world_pos(x, y, 0).truncate() + Vec2::Y * 32.;
+        let inside = top_edge - Vec2::Y * (ZOMBIE_RADIUS + STEP / 2.);
+
+        let diagonal = Vec2::ONE.normalize() * STEP;
+        assert!(!wall_grid.is_open(inside, diagonal));
+        assert!(wall_grid.is_open(inside, Vec2::new(diagonal.x, 0.)));
+    }
+}
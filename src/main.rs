@@ -1,10 +1,32 @@
+mod aim;
+mod animation;
 mod asset_owner;
+mod boss;
+mod character;
+mod collision;
+mod controls;
+mod dodge;
+mod game_over;
 mod game_state;
+mod health;
+mod house;
+mod inventory;
 mod level;
+mod lighting;
+mod lod;
 mod mouse_position;
+mod movement;
+mod perception;
+mod pickup;
 mod player;
 mod primary_camera;
+mod shoebox;
+mod shot;
+mod stamina;
+mod steering;
 mod task;
+mod telegraph;
+mod throwable;
 mod tile;
 mod ui;
 mod zombie;
@@ -33,15 +55,40 @@ fn main() {
                 .in_fixed_schedule(),
             //RapierDebugRenderPlugin::default(),
             InputManagerPlugin::<PlayerAction>::default(),
+        ))
+        .add_plugins((
             game_state::game_state_plugin,
+            shoebox::shoebox_plugin,
+            character::character_plugin,
+            animation::animation_plugin,
             level::level_plugin,
+            house::house_plugin,
             primary_camera::primary_camera_plugin,
+            steering::steering_plugin,
             mouse_position::mouse_position_plugin,
+            perception::perception_plugin,
             ui::ui_plugin,
             task::task_plugin,
             player::player_plugin,
+            health::health_plugin,
+            game_over::game_over_plugin,
+        ))
+        .add_plugins((
             zombie::zombie_plugin,
+            telegraph::telegraph_plugin,
+            lod::lod_plugin,
+            boss::boss_plugin,
+            combat::combat_plugin,
+            shot::shot_plugin,
+            pickup::pickup_plugin,
+            inventory::inventory_plugin,
+            stamina::stamina_plugin,
+            dodge::dodge_plugin,
             tile::tile_plugin,
+            aim::aim_plugin,
+            controls::controls_plugin,
+            lighting::lighting_plugin,
+            throwable::throwable_plugin,
         ))
         .run();
 }
@@ -20,7 +20,7 @@ impl MousePosition {
     }
 }
 
-fn update_mouse_position(
+pub fn update_mouse_position(
     mut old_mouse_pos: ResMut<MousePosition>,
     primary_win_qry: Query<&Window, With<PrimaryWindow>>,
     primary_cam_qry: Query<(&Camera, &GlobalTransform), With<PrimaryCamera>>,
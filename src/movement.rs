@@ -0,0 +1,120 @@
+use bevy::prelude::*;
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct MovementParams {
+    pub max_speed: f32,
+    pub acceleration: f32,
+    pub deceleration: f32,
+}
+
+impl MovementParams {
+    pub fn with_max_speed(self, max_speed: f32) -> Self {
+        Self { max_speed, ..self }
+    }
+}
+
+#[derive(Component)]
+pub struct Movement {
+    params: MovementParams,
+    speed_scale: f32,
+    vel: Vec2,
+}
+
+impl Movement {
+    pub fn new(params: MovementParams) -> Self {
+        Self {
+            params,
+            speed_scale: 1.,
+            vel: Vec2::ZERO,
+        }
+    }
+
+    pub fn max_speed(&self) -> f32 {
+        self.params.max_speed * self.speed_scale
+    }
+
+    pub fn velocity(&self) -> Vec2 {
+        self.vel
+    }
+
+    pub fn set_speed_scale(&mut self, speed_scale: f32) {
+        self.speed_scale = speed_scale;
+    }
+
+    //Moves the velocity towards the input direction at max speed, returning the step's displacement
+    //Input is clamped to unit length so diagonals aren't faster, and no input decelerates to a stop
+    pub fn step(&mut self, input: Vec2, dt: f32) -> Vec2 {
+        let input = input.clamp_length_max(1.);
+        let rate = if input == Vec2::ZERO {
+            self.params.deceleration
+        } else {
+            self.params.acceleration
+        };
+        let target_vel = input * self.max_speed();
+        self.vel += (target_vel - self.vel).clamp_length_max(rate * dt);
+        self.vel * dt
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 1. / 64.;
+    const PARAMS: MovementParams = MovementParams {
+        max_speed: 128.,
+        acceleration: 512.,
+        deceleration: 1024.,
+    };
+
+    fn run(movement: &mut Movement, input: Vec2, ticks: usize) -> Vec2 {
+        (0..ticks).fold(Vec2::ZERO, |_, _| movement.step(input, DT))
+    }
+
+    #[test]
+    fn accelerates_gradually_to_max_speed() {
+        let mut movement = Movement::new(PARAMS);
+        let first_step = movement.step(Vec2::X, DT);
+        assert_eq!(first_step, Vec2::X * PARAMS.acceleration * DT * DT);
+
+        //512 px/s² reaches 128 px/s in a quarter of a second, which is 16 ticks
+        run(&mut movement, Vec2::X, 14);
+        assert!(movement.vel.x < PARAMS.max_speed);
+        run(&mut movement, Vec2::X, 1);
+        assert_eq!(movement.vel, Vec2::X * PARAMS.max_speed);
+    }
+
+    #[test]
+    fn never_exceeds_max_speed() {
+        let mut movement = Movement::new(PARAMS);
+        let step = run(&mut movement, Vec2::new(5., -3.), 256);
+        assert!((step.length() - PARAMS.max_speed * DT).abs() < 1e-4);
+    }
+
+    #[test]
+    fn diagonals_are_not_faster() {
+        let mut straight = Movement::new(PARAMS);
+        let mut diagonal = Movement::new(PARAMS);
+        let straight_step = run(&mut straight, Vec2::Y, 64);
+        let diagonal_step = run(&mut diagonal, Vec2::ONE, 64);
+        assert!((straight_step.length() - diagonal_step.length()).abs() < 1e-4);
+    }
+
+    #[test]
+    fn decelerates_to_a_stop_without_input() {
+        let mut movement = Movement::new(PARAMS);
+        run(&mut movement, Vec2::X, 64);
+
+        //1024 px/s² stops 128 px/s in an eighth of a second, which is 8 ticks
+        run(&mut movement, Vec2::ZERO, 7);
+        assert!(movement.vel.x > 0.);
+        assert_eq!(run(&mut movement, Vec2::ZERO, 1), Vec2::ZERO);
+    }
+
+    #[test]
+    fn speed_is_configurable() {
+        let mut movement = Movement::new(PARAMS.with_max_speed(64.));
+        run(&mut movement, -Vec2::X, 64);
+        assert_eq!(movement.vel, -Vec2::X * 64.);
+    }
+}
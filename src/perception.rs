@@ -0,0 +1,153 @@
+use {
+    super::{
+        game_state::GameState,
+        player::Player,
+        throwable::Stunned,
+        tile::{Wall, AVG_TILE_DIMENSION},
+    },
+    bevy::prelude::*,
+    bevy_rapier2d::prelude::*,
+    rand::Rng,
+};
+
+const SIGHT_RANGE: f32 = AVG_TILE_DIMENSION * 6.;
+const HALF_FIELD_OF_VIEW: f32 = std::f32::consts::FRAC_PI_3;
+const MEMORY_SECS: f32 = 8.;
+const WANDER_RADIUS: f32 = AVG_TILE_DIMENSION * 3.;
+const WANDER_SECS: f32 = 4.;
+pub const FOOTSTEP_NOISE_RADIUS: f32 = AVG_TILE_DIMENSION * 1.5;
+
+#[derive(Event)]
+pub struct NoiseEvent {
+    pub pos: Vec2,
+    pub radius: f32,
+}
+
+#[derive(Component)]
+pub struct Perception {
+    last_known_pos: Option<Vec2>,
+    memory: Timer,
+    wander_pos: Option<Vec2>,
+    wander_timer: Timer,
+}
+
+impl Default for Perception {
+    fn default() -> Self {
+        Self {
+            last_known_pos: None,
+            memory: Timer::from_seconds(MEMORY_SECS, TimerMode::Once),
+            wander_pos: None,
+            wander_timer: Timer::from_seconds(WANDER_SECS, TimerMode::Repeating),
+        }
+    }
+}
+
+impl Perception {
+    pub fn last_known_pos(&self) -> Option<Vec2> {
+        self.last_known_pos
+    }
+
+    pub fn target(&self) -> Option<Vec2> {
+        self.last_known_pos.or(self.wander_pos)
+    }
+
+    fn remember(&mut self, pos: Vec2) {
+        self.last_known_pos = Some(pos);
+        self.memory.reset();
+        self.wander_pos = None;
+    }
+}
+
+pub fn can_see(
+    rapier_ctx: &RapierContext,
+    wall_qry: &Query<(), With<Wall>>,
+    from: Vec2,
+    to: Vec2,
+) -> bool {
+    let dist = from.distance(to);
+    dist == 0.
+        || rapier_ctx
+            .cast_ray(
+                from,
+                (to - from) / dist,
+                dist,
+                true,
+                QueryFilter::new().predicate(&|collider_id| wall_qry.contains(collider_id)),
+            )
+            .is_none()
+}
+
+fn zombie_sight(
+    mut perception_qry: Query<(&mut Perception, &Transform), Without<Stunned>>,
+    player_qry: Query<&Transform, With<Player>>,
+    wall_qry: Query<(), With<Wall>>,
+    rapier_ctx: Res<RapierContext>,
+) {
+    let player_pos = player_qry.single().translation.truncate();
+    for (mut perception, perceiver_xform) in &mut perception_qry {
+        let perceiver_pos = perceiver_xform.translation.truncate();
+        let to_player = player_pos - perceiver_pos;
+        if to_player.length() > SIGHT_RANGE {
+            continue;
+        }
+        let facing = (perceiver_xform.rotation * Vec3::X).truncate();
+        if facing.angle_between(to_player).abs() > HALF_FIELD_OF_VIEW {
+            continue;
+        }
+        if can_see(&rapier_ctx, &wall_qry, perceiver_pos, player_pos) {
+            perception.remember(player_pos);
+        }
+    }
+}
+
+//Stunned zombies are blinded and deafened until they recover
+fn zombie_hearing(
+    mut perception_qry: Query<(&mut Perception, &Transform), Without<Stunned>>,
+    mut noise_evr: EventReader<NoiseEvent>,
+) {
+    for noise in noise_evr.read() {
+        for (mut perception, perceiver_xform) in &mut perception_qry {
+            if perceiver_xform.translation.truncate().distance(noise.pos) <= noise.radius {
+                perception.remember(noise.pos);
+            }
+        }
+    }
+}
+
+fn forget_and_wander(mut perception_qry: Query<(&mut Perception, &Transform)>, time: Res<Time>) {
+    let dt = time.delta();
+    let mut thread_rng = rand::thread_rng();
+    for (mut perception, perceiver_xform) in &mut perception_qry {
+        let perceiver_pos = perceiver_xform.translation.truncate();
+        if let Some(last_known_pos) = perception.last_known_pos {
+            if perception.memory.tick(dt).finished()
+                || perceiver_pos.distance(last_known_pos) <= AVG_TILE_DIMENSION / 2.
+            {
+                perception.last_known_pos = None;
+            }
+            continue;
+        }
+        if perception.wander_pos.is_none() || perception.wander_timer.tick(dt).just_finished() {
+            perception.wander_pos = Some(
+                perceiver_pos
+                    + Vec2::new(
+                        thread_rng.gen_range(-WANDER_RADIUS..=WANDER_RADIUS),
+                        thread_rng.gen_range(-WANDER_RADIUS..=WANDER_RADIUS),
+                    ),
+            );
+        }
+    }
+}
+
+#[derive(SystemSet, Hash, Debug, Clone, Eq, PartialEq)]
+pub struct PerceptionSet;
+
+pub fn perception_plugin(app: &mut App) {
+    app.add_event::<NoiseEvent>().add_systems(
+        FixedUpdate,
+        (zombie_hearing, zombie_sight, forget_and_wander)
+            .chain()
+            .in_set(PerceptionSet)
+            .run_if(in_state(GameState::Playing)),
+    );
+}
@@ -0,0 +1,294 @@
+use {
+    super::{
+        asset_owner::TextureAtlasOwner,
+        game_state::GameState,
+        health::PlayerHealthBar,
+        inventory::{Inventory, Item},
+        level::LevelObject,
+        player::{self, Player, PlayerAction},
+        tile::{Tile, AVG_TILE_DIMENSION},
+    },
+    bevy::prelude::*,
+    leafwing_input_manager::prelude::*,
+    rand::{seq::SliceRandom, Rng},
+};
+
+const PICKUP_REACH: f32 = AVG_TILE_DIMENSION * 0.75;
+const AMMO_PICKUP_ROUNDS: u32 = 24;
+const CONTAINER_LOOT_CHANCE: f64 = 0.5;
+const CONTAINER_LOOT: [LevelObject; 13] = [
+    LevelObject::Bandage,
+    LevelObject::Bandage,
+    LevelObject::Bandage,
+    LevelObject::Bandage,
+    LevelObject::Food,
+    LevelObject::Food,
+    LevelObject::Food,
+    LevelObject::Medkit,
+    LevelObject::Planks,
+    LevelObject::Planks,
+    LevelObject::NoiseMaker,
+    LevelObject::Molotov,
+    LevelObject::Flashbang,
+];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Consumable {
+    Bandage,
+    Food,
+    Medkit,
+}
+
+impl Consumable {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Bandage => "Bandage",
+            Self::Food => "Food",
+            Self::Medkit => "Medkit",
+        }
+    }
+
+    //Medkits share the bandage sprite, tinted red to tell them apart
+    pub fn color(&self) -> Color {
+        match self {
+            Self::Medkit => Color::srgb(1., 0.45, 0.45),
+            _ => Color::WHITE,
+        }
+    }
+
+    fn half_hearts(&self) -> u8 {
+        match self {
+            Self::Bandage => 1,
+            Self::Food => 2,
+            Self::Medkit => PlayerHealthBar::MAX_SIZE as u8 * 2,
+        }
+    }
+
+    fn use_secs(&self) -> f32 {
+        match self {
+            Self::Bandage => 1.,
+            Self::Food => 1.5,
+            Self::Medkit => 2.5,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Container {
+    looted: bool,
+    loot: Option<LevelObject>,
+}
+
+#[derive(Component)]
+struct Pickup(Item);
+
+#[derive(Component)]
+struct PickupPrompt;
+
+//Using a consumable takes time and leaves the player open to hits until it's finished
+#[derive(Component)]
+pub struct UsingConsumable {
+    consumable: Consumable,
+    timer: Timer,
+}
+
+impl UsingConsumable {
+    pub fn new(consumable: Consumable) -> Self {
+        Self {
+            consumable,
+            timer: Timer::from_seconds(consumable.use_secs(), TimerMode::Once),
+        }
+    }
+}
+
+pub fn spawn_container(
+    cmds: &mut Commands,
+    container_pos: Vec3,
+    tile_tex_atlas: &Res<TextureAtlasOwner<Tile>>,
+) {
+    let mut thread_rng = rand::thread_rng();
+    cmds.spawn((
+        Tile,
+        Container {
+            looted: false,
+            loot: thread_rng
+                .gen_bool(CONTAINER_LOOT_CHANCE)
+                .then(|| CONTAINER_LOOT.choose(&mut thread_rng).copied())
+                .flatten(),
+        },
+        StateScoped(GameState::Playing),
+        SpriteBundle {
+            transform: Transform::from_translation(container_pos),
+            texture: tile_tex_atlas.texture(),
+            ..default()
+        },
+        TextureAtlas {
+            layout: tile_tex_atlas.layout(),
+            index: LevelObject::Box.id(),
+        },
+    ));
+}
+
+pub fn spawn_pickup(
+    cmds: &mut Commands,
+    pickup_pos: Vec3,
+    pickup_lvl_obj: LevelObject,
+    tile_tex_atlas: &Res<TextureAtlasOwner<Tile>>,
+) {
+    let Ok(item) = Item::try_from(pickup_lvl_obj) else {
+        return;
+    };
+    cmds.spawn((
+        Pickup(item),
+        StateScoped(GameState::Playing),
+        SpriteBundle {
+            sprite: Sprite {
+                color: item.color(),
+                ..default()
+            },
+            transform: Transform::from_translation(pickup_pos),
+            texture: tile_tex_atlas.texture(),
+            ..default()
+        },
+        TextureAtlas {
+            layout: tile_tex_atlas.layout(),
+            index: pickup_lvl_obj.id(),
+        },
+    ))
+    .with_children(|parent| {
+        parent.spawn((
+            PickupPrompt,
+            Text2dBundle {
+                text: Text::from_section(
+                    format!("[E] {}", item.name()),
+                    TextStyle {
+                        font_size: 16.,
+                        ..default()
+                    },
+                ),
+                transform: Transform::from_xyz(0., AVG_TILE_DIMENSION * 0.5, 1.),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+        ));
+    });
+}
+
+//Containers hand out ammo to players with a firearm, and are only emptied if some of it fit
+//Any other loot inside is dropped next to the container the first time the player reaches it
+fn loot_containers(
+    mut cmds: Commands,
+    mut container_qry: Query<(&mut Container, &Transform, &mut Sprite)>,
+    mut player_qry: Query<(&Transform, &mut Inventory), With<Player>>,
+    tile_tex_atlas: Res<TextureAtlasOwner<Tile>>,
+) {
+    let (player_xform, mut inventory) = player_qry.single_mut();
+    let player_pos = player_xform.translation.truncate();
+    for (mut container, container_xform, mut container_sprite) in &mut container_qry {
+        if container.looted
+            || container_xform.translation.truncate().distance(player_pos) > PICKUP_REACH
+        {
+            continue;
+        }
+        if let Some(loot) = container.loot.take() {
+            let drop_dir =
+                (player_pos - container_xform.translation.truncate()).normalize_or_zero();
+            spawn_pickup(
+                &mut cmds,
+                container_xform.translation + (drop_dir * PICKUP_REACH * 0.5).extend(0.),
+                loot,
+                &tile_tex_atlas,
+            );
+        }
+        if !inventory.has_firearm()
+            || inventory.add(Item::Ammo, AMMO_PICKUP_ROUNDS) < AMMO_PICKUP_ROUNDS
+        {
+            container.looted = true;
+            container_sprite.color = Color::srgb(0.5, 0.5, 0.5);
+        }
+    }
+}
+
+fn show_pickup_prompts(
+    pickup_qry: Query<&Transform, With<Pickup>>,
+    mut prompt_qry: Query<(&Parent, &mut Visibility), With<PickupPrompt>>,
+    player_qry: Query<&Transform, With<Player>>,
+) {
+    let player_pos = player_qry.single().translation.truncate();
+    for (prompt_parent, mut prompt_visibility) in &mut prompt_qry {
+        let in_reach = pickup_qry
+            .get(prompt_parent.get())
+            .is_ok_and(|pickup_xform| {
+                pickup_xform.translation.truncate().distance(player_pos) <= PICKUP_REACH
+            });
+        *prompt_visibility = if in_reach {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+//Pickups stay on the ground while the inventory has no room for them
+fn pick_up_items(
+    mut cmds: Commands,
+    pickup_qry: Query<(Entity, &Pickup, &Transform)>,
+    mut player_qry: Query<(
+        &Player,
+        &Transform,
+        &mut Inventory,
+        &ActionState<PlayerAction>,
+    )>,
+) {
+    let Ok((player, player_xform, mut inventory, player_in)) = player_qry.get_single_mut() else {
+        return;
+    };
+    if player.doing_task() || !player_in.just_pressed(&PlayerAction::PickUp) {
+        return;
+    }
+    let player_pos = player_xform.translation.truncate();
+    let Some((pickup_id, pickup, _)) = pickup_qry
+        .iter()
+        .filter(|(_, _, pickup_xform)| {
+            pickup_xform.translation.truncate().distance(player_pos) <= PICKUP_REACH
+        })
+        .min_by(|(_, _, a_xform), (_, _, b_xform)| {
+            (a_xform.translation.truncate().distance(player_pos))
+                .total_cmp(&b_xform.translation.truncate().distance(player_pos))
+        })
+    else {
+        return;
+    };
+    if inventory.add(pickup.0, 1) == 0 {
+        cmds.entity(pickup_id).despawn_recursive();
+    }
+}
+
+fn use_consumables(
+    mut cmds: Commands,
+    mut player_qry: Query<(Entity, &mut UsingConsumable, &mut PlayerHealthBar)>,
+    time: Res<Time>,
+) {
+    for (player_id, mut using, mut player_hp_bar) in &mut player_qry {
+        if !using.timer.tick(time.delta()).finished() {
+            continue;
+        }
+        if !player_hp_bar.is_empty() {
+            player_hp_bar.heal(using.consumable.half_hearts());
+        }
+        cmds.entity(player_id).remove::<UsingConsumable>();
+    }
+}
+
+pub fn pickup_plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            loot_containers,
+            show_pickup_prompts,
+            pick_up_items.run_if(player::can_act),
+            use_consumables,
+        )
+            .run_if(in_state(GameState::Playing)),
+    );
+}
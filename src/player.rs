@@ -1,14 +1,39 @@
 use {
     super::{
+        aim::AimPosition,
+        animation::{AnimationClip, AnimationMode, AnimationSet, MovementClips, SpriteAnimation},
         asset_owner::TextureAtlasOwner,
-        game_state::GameState,
-        mouse_position::MousePosition,
+        character::{self, CharacterDef},
+        collision::{self, PLAYER_GROUP},
+        combat::{EquippedWeapon, Knockback, WeaponKind},
+        controls::Controls,
+        dodge::{Dodge, DodgeCooldown},
+        game_state::{GameState, SetupAssets},
+        health::{DamageEvent, DamageSource, PlayerHealthBar},
+        inventory::Inventory,
+        movement::{Movement, MovementParams},
+        perception::{NoiseEvent, FOOTSTEP_NOISE_RADIUS},
+        pickup::UsingConsumable,
+        shoebox::ShoeBoxAtlas,
+        stamina::Stamina,
         task::Task,
         tile::{AVG_TILE_DIMENSION, TILE_SIZE},
+        zombie::{Zombie, ZombieAttackEvent},
     },
     bevy::prelude::*,
     bevy_rapier2d::prelude::*,
     leafwing_input_manager::prelude::*,
+    serde::{Deserialize, Serialize},
+    strum_macros::EnumIter,
+};
+
+const ZOMBIE_HIT_KNOCKBACK: f32 = AVG_TILE_DIMENSION * 3.;
+const SPRINT_SPEED_SCALE: f32 = 1.6;
+const SPRINT_COST_PER_SEC: f32 = 25.;
+const PLAYER_MOVEMENT: MovementParams = MovementParams {
+    max_speed: TILE_SIZE.x * 2.,
+    acceleration: TILE_SIZE.x * 16.,
+    deceleration: TILE_SIZE.x * 24.,
 };
 
 #[derive(Component)]
@@ -16,32 +41,118 @@ pub struct Player {
     doing_task: bool,
 }
 
-#[derive(Actionlike, PartialEq, Eq, Hash, Clone, Reflect, Debug)]
+#[derive(
+    Actionlike, EnumIter, PartialEq, Eq, Hash, Clone, Reflect, Debug, Serialize, Deserialize,
+)]
 pub enum PlayerAction {
     MoveLeft,
     MoveRight,
     MoveUp,
     MoveDown,
+    Move,
+    Aim,
     EnterTask,
     ExitTask,
+    Fire,
+    Reload,
+    Sprint,
+    Dodge,
+    PickUp,
+    Hotbar1,
+    Hotbar2,
+    Hotbar3,
+    Hotbar4,
+    Hotbar5,
+    Hotbar6,
+    NextItem,
+    PreviousItem,
 }
 
-#[derive(Resource)]
-pub struct PlayerHealthBar(pub Vec<u8>);
+impl PlayerAction {
+    //The stick actions stay on the gamepad's sticks
+    pub fn is_rebindable(&self) -> bool {
+        !matches!(self, Self::Move | Self::Aim)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::MoveLeft => "Move left",
+            Self::MoveRight => "Move right",
+            Self::MoveUp => "Move up",
+            Self::MoveDown => "Move down",
+            Self::Move => "Move",
+            Self::Aim => "Aim",
+            Self::EnterTask => "Start task",
+            Self::ExitTask => "Leave task",
+            Self::Fire => "Fire",
+            Self::Reload => "Reload",
+            Self::Sprint => "Sprint",
+            Self::Dodge => "Dodge",
+            Self::PickUp => "Pick up",
+            Self::Hotbar1 => "Hotbar 1",
+            Self::Hotbar2 => "Hotbar 2",
+            Self::Hotbar3 => "Hotbar 3",
+            Self::Hotbar4 => "Hotbar 4",
+            Self::Hotbar5 => "Hotbar 5",
+            Self::Hotbar6 => "Hotbar 6",
+            Self::NextItem => "Next item",
+            Self::PreviousItem => "Previous item",
+        }
+    }
+}
 
-impl PlayerHealthBar {
-    pub const MAX_SIZE: usize = 3;
+impl Player {
+    pub fn doing_task(&self) -> bool {
+        self.doing_task
+    }
 
-    fn new() -> Self {
-        Self(vec![2; Self::MAX_SIZE])
+    pub fn stop_task(&mut self) {
+        self.doing_task = false;
     }
 }
 
+#[derive(Component)]
+struct ReloadClip(AnimationClip);
+
+#[derive(Component)]
+struct UseClip(AnimationClip);
+
+//Every pose a character is drawn in, which the player atlas has to have for each of them
+pub const CHARACTER_POSES: [&str; 4] = ["gun", "hold", "reload", "stand"];
+
+fn character_clip(
+    player_tex_atlas: &TextureAtlasOwner<Player>,
+    character: &CharacterDef,
+    poses: &[&str],
+    frame_secs: f32,
+    mode: AnimationMode,
+) -> AnimationClip {
+    let frames = poses
+        .iter()
+        .map(|pose| {
+            player_tex_atlas
+                .index(&character.sprite(pose))
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+    AnimationClip::new(frames, frame_secs, mode)
+}
+
 pub fn spawn_player(
     cmds: &mut Commands,
     player_pos: Vec2,
     player_tex_atlas: &Res<TextureAtlasOwner<Player>>,
+    character: &CharacterDef,
+    controls: &Controls,
 ) {
+    let clip = |poses: &[&str], frame_secs, mode| {
+        character_clip(player_tex_atlas, character, poses, frame_secs, mode)
+    };
+    let idle = clip(&["gun"], 1., AnimationMode::Looping);
+    let reload_secs = match character.weapon.kind {
+        WeaponKind::Firearm { reload_secs, .. } => reload_secs,
+        WeaponKind::Melee { .. } => 0.,
+    };
     cmds.spawn((
         Player { doing_task: false },
         StateScoped(GameState::Playing),
@@ -50,32 +161,39 @@ pub fn spawn_player(
             transform: Transform::from_translation(player_pos.extend(10.)),
             ..default()
         },
-        TextureAtlas {
-            layout: player_tex_atlas.layout(),
-            index: 0,
-        },
+        player_tex_atlas.animated(idle.clone()),
+        (
+            MovementClips {
+                idle,
+                walk: clip(&["gun", "hold"], 0.2, AnimationMode::Looping),
+            },
+            ReloadClip(clip(&["reload"], reload_secs, AnimationMode::OneShot)),
+            UseClip(clip(&["stand"], 0.1, AnimationMode::OneShot)),
+        ),
         KinematicCharacterController::default(),
         Collider::ball(15.),
-        InputManagerBundle::with_map(
-            InputMap::new([
-                (PlayerAction::MoveLeft, KeyCode::KeyA),
-                (PlayerAction::MoveRight, KeyCode::KeyD),
-                (PlayerAction::MoveUp, KeyCode::KeyW),
-                (PlayerAction::MoveDown, KeyCode::KeyS),
-                (PlayerAction::ExitTask, KeyCode::Escape),
-            ])
-            .with(PlayerAction::EnterTask, MouseButton::Left),
-        ),
-        Velocity::linear(TILE_SIZE * 2.),
+        collision::groups(PLAYER_GROUP),
+        InputManagerBundle::with_map(controls.input_map()),
+        EquippedWeapon::new(character.weapon),
+        Inventory::new(character.weapon),
+        Stamina::default(),
+        PlayerHealthBar::default(),
+        DodgeCooldown::default(),
+        Movement::new(PLAYER_MOVEMENT.with_max_speed(PLAYER_MOVEMENT.max_speed * character.speed)),
     ));
 }
 
-//If player is within proximity to a task, when left mouse is clicked enter that task
-fn player_task_input(
-    mut player_qry: Query<(&mut Player, &Transform, &ActionState<PlayerAction>)>,
-    task_qry: Query<&Transform, With<Task>>,
+//If player is within proximity to a task, when left mouse or south is pressed enter that task
+pub fn player_task_input(
+    mut player_qry: Query<(
+        &mut Player,
+        &Transform,
+        &Inventory,
+        &ActionState<PlayerAction>,
+    )>,
+    task_qry: Query<(&Task, &Transform)>,
 ) {
-    let (mut player, player_xform, player_in) = player_qry.single_mut();
+    let (mut player, player_xform, inventory, player_in) = player_qry.single_mut();
 
     if player.doing_task {
         if player_in.just_pressed(&PlayerAction::ExitTask) {
@@ -85,10 +203,10 @@ fn player_task_input(
     }
     let player_pos = player_xform.translation.truncate();
 
-    let Some(closest_task_pos) = task_qry
+    let Some((closest_task, closest_task_pos)) = task_qry
         .iter()
-        .map(|task_xform| task_xform.translation.truncate())
-        .min_by(|task_a_pos, task_b_pos| {
+        .map(|(task, task_xform)| (task, task_xform.translation.truncate()))
+        .min_by(|(_, task_a_pos), (_, task_b_pos)| {
             (task_a_pos.distance(player_pos)).total_cmp(&task_b_pos.distance(player_pos))
         })
     else {
@@ -97,75 +215,193 @@ fn player_task_input(
 
     if player_in.just_pressed(&PlayerAction::EnterTask)
         && closest_task_pos.distance(player_pos) <= AVG_TILE_DIMENSION
+        && closest_task
+            .required_item()
+            .is_none_or(|item| inventory.contains(item))
     {
         player.doing_task = true;
     }
 }
 
-fn player_movement(
-    mut player_qry: Query<(
-        &Player,
-        &mut KinematicCharacterController,
-        &mut Transform,
-        &ActionState<PlayerAction>,
-        &Velocity,
-    )>,
-    mouse_pos: Res<MousePosition>,
+#[allow(clippy::type_complexity)]
+pub fn player_movement(
+    mut player_qry: Query<
+        (
+            &Player,
+            &mut KinematicCharacterController,
+            &mut Transform,
+            &ActionState<PlayerAction>,
+            &mut Movement,
+            &mut Stamina,
+            Has<UsingConsumable>,
+        ),
+        (Without<Dodge>, Without<Knockback>),
+    >,
+    aim_pos: Res<AimPosition>,
+    mut noise_evw: EventWriter<NoiseEvent>,
     time: Res<Time>,
 ) {
     let dt = time.delta_seconds();
-    let (player, mut player_kcc, mut player_xform, player_in, player_vel) = player_qry.single_mut();
-    if player.doing_task {
+    let Ok((
+        player,
+        mut player_kcc,
+        mut player_xform,
+        player_in,
+        mut player_movement,
+        mut stamina,
+        using_consumable,
+    )) = player_qry.get_single_mut()
+    else {
+        return;
+    };
+    if player.doing_task || using_consumable {
+        player_kcc.translation = Some(player_movement.step(Vec2::ZERO, dt));
         return;
     };
 
-    let theta = -(mouse_pos.as_vec() - player_xform.translation.truncate()).angle_between(Vec2::X);
+    let theta = -(aim_pos.as_vec() - player_xform.translation.truncate()).angle_between(Vec2::X);
     player_xform.rotation = Quat::from_rotation_z(theta);
 
-    let mut displacement = Vec2::ZERO;
+    //Keys and the left stick add together, and the step clamps the result back to unit length
+    let mut input = player_in.axis_pair(&PlayerAction::Move);
 
     if player_in.pressed(&PlayerAction::MoveLeft) {
-        displacement.x -= player_vel.linvel.x * dt;
+        input.x -= 1.;
     }
     if player_in.pressed(&PlayerAction::MoveRight) {
-        displacement.x += player_vel.linvel.x * dt;
+        input.x += 1.;
     }
     if player_in.pressed(&PlayerAction::MoveUp) {
-        displacement.y += player_vel.linvel.y * dt;
+        input.y += 1.;
     }
     if player_in.pressed(&PlayerAction::MoveDown) {
-        displacement.y -= player_vel.linvel.y * dt;
+        input.y -= 1.;
+    }
+    let sprinting = input != Vec2::ZERO
+        && player_in.pressed(&PlayerAction::Sprint)
+        && stamina.spend(SPRINT_COST_PER_SEC * dt);
+    player_movement.set_speed_scale(if sprinting { SPRINT_SPEED_SCALE } else { 1. });
+    if input != Vec2::ZERO {
+        noise_evw.send(NoiseEvent {
+            pos: player_xform.translation.truncate(),
+            radius: FOOTSTEP_NOISE_RADIUS,
+        });
+    }
+    player_kcc.translation = Some(player_movement.step(input, dt));
+}
+
+//The reload pose is held for as long as the reload takes, and dropped if the reload is abandoned
+fn play_reload_clip(
+    mut player_qry: Query<
+        (
+            &EquippedWeapon,
+            &ReloadClip,
+            &MovementClips,
+            &mut SpriteAnimation,
+        ),
+        With<Player>,
+    >,
+    mut was_reloading: Local<bool>,
+) {
+    for (weapon, reload_clip, movement_clips, mut player_anim) in &mut player_qry {
+        let reloading = weapon.is_reloading();
+        if reloading && !*was_reloading {
+            player_anim.play(reload_clip.0.clone());
+        } else if !reloading && player_anim.is_playing(&reload_clip.0) {
+            player_anim.play(movement_clips.idle.clone());
+        }
+        *was_reloading = reloading;
+    }
+}
+
+//Running out of health stops the player from moving, working on tasks or scoring
+pub fn is_alive(player_qry: Query<&PlayerHealthBar, With<Player>>) -> bool {
+    player_qry
+        .get_single()
+        .is_ok_and(|player_hp_bar| !player_hp_bar.is_empty())
+}
+
+//Actions are also locked out while a consumable is being used
+pub fn can_act(player_qry: Query<(&PlayerHealthBar, Has<UsingConsumable>), With<Player>>) -> bool {
+    player_qry
+        .get_single()
+        .is_ok_and(|(player_hp_bar, using_consumable)| {
+            !player_hp_bar.is_empty() && !using_consumable
+        })
+}
+
+//Holding the use pose shows the player has lowered their weapon while patching up
+#[allow(clippy::type_complexity)]
+fn play_use_clip(
+    mut player_qry: Query<(&UseClip, &mut SpriteAnimation), (With<Player>, With<UsingConsumable>)>,
+) {
+    for (use_clip, mut player_anim) in &mut player_qry {
+        player_anim.play(use_clip.0.clone());
+    }
+}
+
+fn take_zombie_hits(
+    mut attack_evr: EventReader<ZombieAttackEvent>,
+    mut damage_evw: EventWriter<DamageEvent>,
+    player_qry: Query<(Entity, &Transform), With<Player>>,
+    zombie_qry: Query<&Transform, With<Zombie>>,
+) {
+    let (player_id, player_xform) = player_qry.single();
+    for attack in attack_evr.read() {
+        if attack.target_id != player_id {
+            continue;
+        }
+        let knockback_dir = zombie_qry
+            .get(attack.zombie_id)
+            .map_or(Vec2::ZERO, |zombie_xform| {
+                (player_xform.translation - zombie_xform.translation)
+                    .truncate()
+                    .normalize_or_zero()
+            });
+        damage_evw.send(DamageEvent {
+            target_id: player_id,
+            half_hearts: 1,
+            source: DamageSource::Zombie,
+            knockback: knockback_dir * ZOMBIE_HIT_KNOCKBACK,
+        });
     }
-    player_kcc.translation = Some(displacement);
 }
 
 pub fn player_plugin(app: &mut App) {
     app.add_systems(
         OnEnter(GameState::Setup),
+        |asset_server: Res<AssetServer>, mut setup_assets: ResMut<SetupAssets>| {
+            setup_assets.track(asset_server.load::<ShoeBoxAtlas>("player.xml"));
+        },
+    )
+    .add_systems(
+        OnExit(GameState::Setup),
         |mut cmds: Commands,
          asset_server: Res<AssetServer>,
-         mut tex_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>| {
-            cmds.insert_resource(TextureAtlasOwner::<Player>::new(
-                asset_server.load("player.png"),
-                tex_atlas_layouts.add(TextureAtlasLayout::from_grid(
-                    UVec2::splat(64),
-                    2,
-                    2,
-                    None,
-                    None,
-                )),
-            ))
+         shoebox_atlases: Res<Assets<ShoeBoxAtlas>>| {
+            let shoebox_atlas = shoebox_atlases
+                .get(&asset_server.load::<ShoeBoxAtlas>("player.xml"))
+                .expect("player.xml is loaded during setup");
+            let player_tex_atlas = TextureAtlasOwner::<Player>::from_shoebox(shoebox_atlas);
+            cmds.insert_resource(character::available_characters(&player_tex_atlas));
+            cmds.insert_resource(player_tex_atlas);
         },
     )
-    .add_systems(OnEnter(GameState::Playing), |mut cmds: Commands| {
-        cmds.insert_resource(PlayerHealthBar::new())
-    })
     .add_systems(
         Update,
-        player_task_input.run_if(in_state(GameState::Playing)),
+        (
+            player_task_input.run_if(can_act),
+            take_zombie_hits,
+            (play_reload_clip, play_use_clip)
+                .chain()
+                .before(AnimationSet),
+        )
+            .run_if(in_state(GameState::Playing)),
     )
     .add_systems(
         FixedUpdate,
-        player_movement.run_if(in_state(GameState::Playing)),
+        player_movement
+            .run_if(in_state(GameState::Playing))
+            .run_if(is_alive),
     );
 }
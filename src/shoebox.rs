@@ -0,0 +1,168 @@
+use {
+    bevy::{
+        asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+        prelude::*,
+        utils::HashMap,
+    },
+    std::io::{Error, ErrorKind},
+    xml::reader::{EventReader, XmlEvent},
+};
+
+//A ShoeBox sprite sheet descriptor: named sub textures packed into a single image
+#[derive(Asset, TypePath)]
+pub struct ShoeBoxAtlas {
+    pub image: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+    names: HashMap<String, usize>,
+}
+
+impl ShoeBoxAtlas {
+    pub fn names(&self) -> &HashMap<String, usize> {
+        &self.names
+    }
+}
+
+#[derive(Default)]
+struct ShoeBoxAtlasLoader;
+
+fn invalid_data(msg: String) -> Error {
+    Error::new(ErrorKind::InvalidData, msg)
+}
+
+//Returns the packed image's path and each sub texture's name, without its extension, and bounds
+fn parse_shoebox(xml: &[u8]) -> Result<(String, Vec<(String, URect)>), Error> {
+    let mut image_path = None;
+    let mut sub_textures = Vec::new();
+    for event in EventReader::new(xml) {
+        let XmlEvent::StartElement {
+            name, attributes, ..
+        } = event.map_err(|err| invalid_data(err.to_string()))?
+        else {
+            continue;
+        };
+        let element = name.local_name.as_str();
+        let value = |attribute_name: &str| {
+            attributes
+                .iter()
+                .find(|attribute| attribute.name.local_name == attribute_name)
+                .map(|attribute| attribute.value.as_str())
+                .ok_or_else(|| invalid_data(format!("{element} is missing `{attribute_name}`")))
+        };
+        let number = |attribute_name: &str| {
+            value(attribute_name)?.parse::<u32>().map_err(|err| {
+                invalid_data(format!("{element} `{attribute_name}` is invalid: {err}"))
+            })
+        };
+        match element {
+            "TextureAtlas" => image_path = Some(value("imagePath")?.to_string()),
+            "SubTexture" => {
+                let name = value("name")?;
+                let name = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+                let min = UVec2::new(number("x")?, number("y")?);
+                let size = UVec2::new(number("width")?, number("height")?);
+                sub_textures.push((name.to_string(), URect::from_corners(min, min + size)));
+            }
+            _ => (),
+        }
+    }
+    let image_path =
+        image_path.ok_or_else(|| invalid_data("TextureAtlas is missing".to_string()))?;
+    Ok((image_path, sub_textures))
+}
+
+impl AssetLoader for ShoeBoxAtlasLoader {
+    type Asset = ShoeBoxAtlas;
+    type Settings = ();
+    type Error = Error;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut xml = Vec::new();
+        reader.read_to_end(&mut xml).await?;
+        let (image_path, sub_textures) = parse_shoebox(&xml)?;
+
+        //The image is loaded directly rather than as a dependency since the layout needs its size
+        let image_path = load_context
+            .asset_path()
+            .resolve_embed(&image_path)
+            .map_err(|err| invalid_data(err.to_string()))?;
+        let image = load_context
+            .loader()
+            .direct()
+            .load::<Image>(image_path)
+            .await
+            .map_err(|err| invalid_data(err.to_string()))?;
+        let mut layout = TextureAtlasLayout::new_empty(image.get().size());
+        let names = sub_textures
+            .into_iter()
+            .map(|(name, rect)| (name, layout.add_texture(rect)))
+            .collect();
+
+        Ok(ShoeBoxAtlas {
+            image: load_context.add_loaded_labeled_asset("image", image),
+            layout: load_context.add_labeled_asset("layout".to_string(), layout),
+            names,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["xml"]
+    }
+}
+
+pub fn shoebox_plugin(app: &mut App) {
+    app.init_asset::<ShoeBoxAtlas>()
+        .init_asset_loader::<ShoeBoxAtlasLoader>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sub_texture(attributes: &str) -> String {
+        format!(r#"<TextureAtlas imagePath="sheet.png"><SubTexture {attributes}/></TextureAtlas>"#)
+    }
+
+    #[test]
+    fn parses_image_path_and_sub_textures() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<TextureAtlas imagePath="sprites.png">
+	<!-- Created with ShoeBox -->
+	<SubTexture name="hitman1_gun.png"	x="164"	y="88"	width="49"	height="43" frameX="-0"/>
+	<SubTexture name="robot1_hold.png"	x="0"	y="0"	width="35"	height="43" frameX="-2"/>
+</TextureAtlas>"#;
+        let (image_path, sub_textures) = parse_shoebox(xml.as_bytes()).unwrap();
+        assert_eq!(image_path, "sprites.png");
+        assert_eq!(
+            sub_textures,
+            [
+                ("hitman1_gun".to_string(), URect::new(164, 88, 213, 131)),
+                ("robot1_hold".to_string(), URect::new(0, 0, 35, 43)),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_missing_attributes() {
+        let xml = sub_texture(r#"name="a.png" x="0" y="0" width="1""#);
+        let err = parse_shoebox(xml.as_bytes()).unwrap_err();
+        assert_eq!(err.to_string(), "SubTexture is missing `height`");
+    }
+
+    #[test]
+    fn rejects_invalid_numbers() {
+        let xml = sub_texture(r#"name="a.png" x="-1" y="0" width="1" height="1""#);
+        let err = parse_shoebox(xml.as_bytes()).unwrap_err();
+        assert!(err.to_string().starts_with("SubTexture `x` is invalid"));
+    }
+
+    #[test]
+    fn rejects_a_missing_image_path() {
+        assert!(parse_shoebox(b"<TextureAtlas/>").is_err());
+        assert!(parse_shoebox(b"<SubTextures/>").is_err());
+    }
+}
@@ -0,0 +1,177 @@
+use {
+    super::{
+        collision::{SHOT_GROUP, WALL_GROUP, ZOMBIE_GROUP},
+        combat::{ShotFiredEvent, ShotKind},
+        game_state::GameState,
+    },
+    bevy::prelude::*,
+    bevy_rapier2d::prelude::*,
+};
+
+const PROJECTILE_RADIUS: f32 = 3.;
+const PROJECTILE_SECS: f32 = 1.;
+const IMPACT_SECS: f32 = 0.15;
+
+#[derive(Event)]
+pub struct ShotHitEvent {
+    pub target_id: Entity,
+    pub point: Vec2,
+    pub normal: Vec2,
+    pub damage: f32,
+}
+
+#[derive(Component)]
+pub struct Projectile {
+    shooter_id: Entity,
+    vel: Vec2,
+    damage: f32,
+    lifetime: Timer,
+}
+
+#[derive(Component)]
+struct Impact(Timer);
+
+pub fn shot_filter(shooter_id: Entity) -> QueryFilter<'static> {
+    QueryFilter::new()
+        .groups(CollisionGroups::new(SHOT_GROUP, WALL_GROUP | ZOMBIE_GROUP))
+        .exclude_collider(shooter_id)
+}
+
+fn resolve_shots(
+    mut cmds: Commands,
+    mut shot_fired_evr: EventReader<ShotFiredEvent>,
+    mut shot_hit_evw: EventWriter<ShotHitEvent>,
+    rapier_ctx: Res<RapierContext>,
+) {
+    for shot in shot_fired_evr.read() {
+        match shot.shot {
+            ShotKind::Hitscan { range } => {
+                let Some((target_id, hit)) = rapier_ctx.cast_ray_and_get_normal(
+                    shot.origin,
+                    shot.dir,
+                    range,
+                    true,
+                    shot_filter(shot.shooter_id),
+                ) else {
+                    continue;
+                };
+                shot_hit_evw.send(ShotHitEvent {
+                    target_id,
+                    point: hit.point,
+                    normal: hit.normal,
+                    damage: shot.damage,
+                });
+            }
+            ShotKind::Projectile { speed } => {
+                cmds.spawn((
+                    StateScoped(GameState::Playing),
+                    Projectile {
+                        shooter_id: shot.shooter_id,
+                        vel: shot.dir * speed,
+                        damage: shot.damage,
+                        lifetime: Timer::from_seconds(PROJECTILE_SECS, TimerMode::Once),
+                    },
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::Srgba(Srgba::rgb_u8(255, 220, 90)),
+                            custom_size: Some(Vec2::new(8., 2.)),
+                            ..default()
+                        },
+                        transform: Transform::from_translation(shot.origin.extend(9.))
+                            .with_rotation(Quat::from_rotation_z(-shot.dir.angle_between(Vec2::X))),
+                        ..default()
+                    },
+                ));
+            }
+        }
+    }
+}
+
+fn move_projectiles(
+    mut cmds: Commands,
+    mut projectile_qry: Query<(Entity, &mut Projectile, &mut Transform)>,
+    mut shot_hit_evw: EventWriter<ShotHitEvent>,
+    rapier_ctx: Res<RapierContext>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_seconds();
+    for (projectile_id, mut projectile, mut projectile_xform) in &mut projectile_qry {
+        if projectile.lifetime.tick(time.delta()).finished() {
+            cmds.entity(projectile_id).despawn_recursive();
+            continue;
+        }
+        let projectile_pos = projectile_xform.translation.truncate();
+        let Some((target_id, hit)) = rapier_ctx.cast_shape(
+            projectile_pos,
+            0.,
+            projectile.vel,
+            &Collider::ball(PROJECTILE_RADIUS),
+            ShapeCastOptions {
+                max_time_of_impact: dt,
+                stop_at_penetration: true,
+                ..default()
+            },
+            shot_filter(projectile.shooter_id),
+        ) else {
+            projectile_xform.translation += (projectile.vel * dt).extend(0.);
+            continue;
+        };
+        let dir = projectile.vel.normalize_or_zero();
+        shot_hit_evw.send(ShotHitEvent {
+            target_id,
+            point: hit.details.map_or(
+                projectile_pos + projectile.vel * hit.time_of_impact,
+                |details| details.witness1,
+            ),
+            normal: hit.details.map_or(-dir, |details| details.normal1),
+            damage: projectile.damage,
+        });
+        cmds.entity(projectile_id).despawn_recursive();
+    }
+}
+
+fn spawn_impacts(mut cmds: Commands, mut shot_hit_evr: EventReader<ShotHitEvent>) {
+    for shot_hit in shot_hit_evr.read() {
+        cmds.spawn((
+            StateScoped(GameState::Playing),
+            Impact(Timer::from_seconds(IMPACT_SECS, TimerMode::Once)),
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::Srgba(Srgba::rgb_u8(255, 240, 180)),
+                    custom_size: Some(Vec2::new(6., 3.)),
+                    ..default()
+                },
+                transform: Transform::from_translation(shot_hit.point.extend(9.)).with_rotation(
+                    Quat::from_rotation_z(-shot_hit.normal.angle_between(Vec2::X)),
+                ),
+                ..default()
+            },
+        ));
+    }
+}
+
+fn fade_impacts(
+    mut cmds: Commands,
+    mut impact_qry: Query<(Entity, &mut Impact, &mut Sprite)>,
+    time: Res<Time>,
+) {
+    for (impact_id, mut impact, mut impact_sprite) in &mut impact_qry {
+        if impact.0.tick(time.delta()).finished() {
+            cmds.entity(impact_id).despawn_recursive();
+            continue;
+        }
+        impact_sprite.color.set_alpha(impact.0.fraction_remaining());
+    }
+}
+
+pub fn shot_plugin(app: &mut App) {
+    app.add_event::<ShotHitEvent>()
+        .add_systems(
+            Update,
+            (resolve_shots, spawn_impacts, fade_impacts).run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(
+            FixedUpdate,
+            move_projectiles.run_if(in_state(GameState::Playing)),
+        );
+}
@@ -0,0 +1,92 @@
+use {
+    super::{
+        combat::Knockback,
+        game_state::GameState,
+        player::{self, Player},
+        tile::AVG_TILE_DIMENSION,
+        zombie::Zombie,
+    },
+    bevy::prelude::*,
+    bevy_rapier2d::prelude::*,
+};
+
+const REGEN_PER_SEC: f32 = 30.;
+const REGEN_DELAY_SECS: f32 = 1.;
+const PUSH_COST_PER_SEC: f32 = 40.;
+const PUSH_SPEED: f32 = AVG_TILE_DIMENSION * 2.;
+
+#[derive(Component)]
+pub struct Stamina {
+    current: f32,
+    regen_delay: Timer,
+}
+
+impl Default for Stamina {
+    fn default() -> Self {
+        Self {
+            current: Self::MAX,
+            regen_delay: Timer::from_seconds(REGEN_DELAY_SECS, TimerMode::Once),
+        }
+    }
+}
+
+impl Stamina {
+    pub const MAX: f32 = 100.;
+
+    pub fn fraction(&self) -> f32 {
+        self.current / Self::MAX
+    }
+
+    //Spending only succeeds if the full amount is available, and then holds off regeneration
+    pub fn spend(&mut self, amount: f32) -> bool {
+        if self.current < amount {
+            return false;
+        }
+        self.current -= amount;
+        self.regen_delay.reset();
+        true
+    }
+}
+
+fn regenerate_stamina(mut stamina_qry: Query<&mut Stamina>, time: Res<Time>) {
+    for mut stamina in &mut stamina_qry {
+        if stamina.regen_delay.tick(time.delta()).finished() {
+            stamina.current =
+                (stamina.current + REGEN_PER_SEC * time.delta_seconds()).min(Stamina::MAX);
+        }
+    }
+}
+
+//Walking into zombies shoves them out of the way for as long as the player has stamina to spare
+fn push_through_zombies(
+    mut cmds: Commands,
+    mut player_qry: Query<(&mut Stamina, &KinematicCharacterControllerOutput), With<Player>>,
+    zombie_qry: Query<(), With<Zombie>>,
+    time: Res<Time>,
+) {
+    let Ok((mut stamina, player_kcc_out)) = player_qry.get_single_mut() else {
+        return;
+    };
+    let push_dir = player_kcc_out.desired_translation.normalize_or_zero();
+    if push_dir == Vec2::ZERO {
+        return;
+    }
+    for collision in &player_kcc_out.collisions {
+        if !zombie_qry.contains(collision.entity)
+            || !stamina.spend(PUSH_COST_PER_SEC * time.delta_seconds())
+        {
+            continue;
+        }
+        cmds.entity(collision.entity)
+            .insert(Knockback::new(push_dir * PUSH_SPEED));
+    }
+}
+
+pub fn stamina_plugin(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        (regenerate_stamina, push_through_zombies)
+            .after(player::player_movement)
+            .run_if(in_state(GameState::Playing)),
+    );
+}
@@ -0,0 +1,195 @@
+use {
+    super::{
+        game_state::GameState,
+        house::{HouseTarget, ZombieGoal},
+        lod::ZombieLod,
+        perception::{Perception, PerceptionSet},
+        tile::{Wall, AVG_TILE_DIMENSION},
+        zombie::Zombie,
+    },
+    bevy::{prelude::*, utils::HashMap},
+    bevy_rapier2d::prelude::*,
+    rand::Rng,
+    std::f32::consts::TAU,
+};
+
+const NEIGHBOUR_RADIUS: f32 = AVG_TILE_DIMENSION;
+const SEPARATION_RADIUS: f32 = AVG_TILE_DIMENSION / 2.;
+const AVOIDANCE_DISTANCE: f32 = AVG_TILE_DIMENSION;
+const SURROUND_RANGE: f32 = AVG_TILE_DIMENSION * 3.;
+const SURROUND_RADIUS: f32 = AVG_TILE_DIMENSION * 0.6;
+const SEEK_WEIGHT: f32 = 1.;
+const SEPARATION_WEIGHT: f32 = 1.5;
+const ALIGNMENT_WEIGHT: f32 = 0.3;
+const AVOIDANCE_WEIGHT: f32 = 2.;
+
+#[derive(Resource, Default)]
+pub struct SpatialHash(HashMap<IVec2, Vec<(Entity, Vec2)>>);
+
+impl SpatialHash {
+    const CELL_SIZE: f32 = AVG_TILE_DIMENSION;
+
+    fn cell(pos: Vec2) -> IVec2 {
+        (pos / Self::CELL_SIZE).floor().as_ivec2()
+    }
+
+    fn insert(&mut self, id: Entity, pos: Vec2) {
+        self.0.entry(Self::cell(pos)).or_default().push((id, pos));
+    }
+
+    pub fn neighbours(&self, pos: Vec2, radius: f32) -> impl Iterator<Item = (Entity, Vec2)> + '_ {
+        let (min_cell, max_cell) = (
+            Self::cell(pos - Vec2::splat(radius)),
+            Self::cell(pos + Vec2::splat(radius)),
+        );
+        (min_cell.y..=max_cell.y)
+            .flat_map(move |y| (min_cell.x..=max_cell.x).map(move |x| IVec2::new(x, y)))
+            .filter_map(|cell| self.0.get(&cell))
+            .flatten()
+            .copied()
+            .filter(move |(_, other_pos)| other_pos.distance(pos) <= radius)
+    }
+}
+
+#[derive(Component)]
+pub struct Steering {
+    heading: Vec2,
+    surround_angle: f32,
+}
+
+impl Default for Steering {
+    fn default() -> Self {
+        Self {
+            heading: Vec2::ZERO,
+            surround_angle: rand::thread_rng().gen_range(0.0..TAU),
+        }
+    }
+}
+
+impl Steering {
+    pub fn heading(&self) -> Vec2 {
+        self.heading
+    }
+}
+
+fn rebuild_spatial_hash(
+    mut spatial_hash: ResMut<SpatialHash>,
+    zombie_qry: Query<(Entity, &Transform), With<Zombie>>,
+) {
+    spatial_hash.0.values_mut().for_each(Vec::clear);
+    for (zombie_id, zombie_xform) in &zombie_qry {
+        spatial_hash.insert(zombie_id, zombie_xform.translation.truncate());
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn zombie_steering(
+    mut zombie_qry: Query<
+        (
+            Entity,
+            &Transform,
+            &Perception,
+            &mut Steering,
+            Option<&ZombieGoal>,
+            &ZombieLod,
+        ),
+        With<Zombie>,
+    >,
+    house_target_qry: Query<&Transform, With<HouseTarget>>,
+    wall_qry: Query<(), With<Wall>>,
+    spatial_hash: Res<SpatialHash>,
+    rapier_ctx: Res<RapierContext>,
+) {
+    let headings = zombie_qry
+        .iter()
+        .map(
+            |(
+                zombie_id,
+                zombie_xform,
+                zombie_perception,
+                zombie_steering,
+                zombie_goal,
+                zombie_lod,
+            )| {
+                let zombie_pos = zombie_xform.translation.truncate();
+                let house_target_pos = match zombie_goal {
+                    Some(&ZombieGoal::House(house_target_id)) => house_target_qry
+                        .get(house_target_id)
+                        .ok()
+                        .map(|house_target_xform| house_target_xform.translation.truncate()),
+                    _ => None,
+                };
+                let Some(mut target) = zombie_perception
+                    .last_known_pos()
+                    .or(house_target_pos)
+                    .or(zombie_perception.target())
+                else {
+                    return (zombie_id, Vec2::ZERO);
+                };
+                if zombie_pos.distance(target) <= SURROUND_RANGE {
+                    target += Vec2::from_angle(zombie_steering.surround_angle) * SURROUND_RADIUS;
+                }
+                let seek = (target - zombie_pos).normalize_or_zero();
+
+                let (mut separation, mut alignment) = (Vec2::ZERO, Vec2::ZERO);
+                for (other_id, other_pos) in spatial_hash.neighbours(zombie_pos, NEIGHBOUR_RADIUS) {
+                    if other_id == zombie_id {
+                        continue;
+                    }
+                    let away = zombie_pos - other_pos;
+                    if away.length() <= SEPARATION_RADIUS {
+                        separation +=
+                            away.normalize_or_zero() * (1. - away.length() / SEPARATION_RADIUS);
+                    }
+                    if let Ok((_, _, _, other_steering, _, _)) = zombie_qry.get(other_id) {
+                        alignment += other_steering.heading;
+                    }
+                }
+
+                let avoidance = (*zombie_lod == ZombieLod::Full)
+                    .then(|| {
+                        rapier_ctx.cast_ray_and_get_normal(
+                            zombie_pos,
+                            seek,
+                            AVOIDANCE_DISTANCE,
+                            true,
+                            QueryFilter::new()
+                                .predicate(&|collider_id| wall_qry.contains(collider_id)),
+                        )
+                    })
+                    .flatten()
+                    .map(|(_, hit)| hit.normal * (1. - hit.time_of_impact / AVOIDANCE_DISTANCE))
+                    .unwrap_or(Vec2::ZERO);
+
+                (
+                    zombie_id,
+                    (seek * SEEK_WEIGHT
+                        + separation * SEPARATION_WEIGHT
+                        + alignment.normalize_or_zero() * ALIGNMENT_WEIGHT
+                        + avoidance * AVOIDANCE_WEIGHT)
+                        .normalize_or_zero(),
+                )
+            },
+        )
+        .collect::<Vec<_>>();
+
+    for (zombie_id, heading) in headings {
+        if let Ok((_, _, _, mut zombie_steering, _, _)) = zombie_qry.get_mut(zombie_id) {
+            zombie_steering.heading = heading;
+        }
+    }
+}
+
+#[derive(SystemSet, Hash, Debug, Clone, Eq, PartialEq)]
+pub struct SteeringSet;
+
+pub fn steering_plugin(app: &mut App) {
+    app.insert_resource(SpatialHash::default()).add_systems(
+        FixedUpdate,
+        (rebuild_spatial_hash, zombie_steering)
+            .chain()
+            .in_set(SteeringSet)
+            .after(PerceptionSet)
+            .run_if(in_state(GameState::Playing)),
+    );
+}
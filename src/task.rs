@@ -1,19 +1,23 @@
 use {
     super::{
+        aim::AimPosition,
         game_state::GameState,
+        health::{DamageEvent, DamageSource},
+        inventory::{Inventory, Item},
         level::{self, LevelObject, LEVEL_LAYOUT, LEVEL_SIZE},
-        mouse_position::MousePosition,
-        player::{Player, PlayerHealthBar},
+        player::Player,
         tile::AVG_TILE_DIMENSION,
         player,
     },
     bevy::prelude::*,
     rand::Rng,
-    std::collections::VecDeque,
+    std::{collections::VecDeque, time::Duration},
     strum::EnumCount,
     strum_macros::EnumCount as EnumCountMacro,
 };
 
+const TASK_WORK_SECS: f32 = 4.;
+
 #[derive(Component, EnumCountMacro, PartialEq, Eq, Clone, Copy)]
 pub enum Task {
     PatchLeak,
@@ -32,9 +36,23 @@ impl Task {
         }
     }
 
+    //Tools are kept once the task is done while materials like planks are used up
+    pub fn required_item(&self) -> Option<Item> {
+        match self {
+            Self::ExtinguishFire => Some(Item::FireExtinguisher),
+            Self::BoardWindow => Some(Item::Planks),
+            _ => None,
+        }
+    }
+
+    fn uses_up_required_item(&self) -> bool {
+        *self == Self::BoardWindow
+    }
+
     pub fn level_object(&self) -> LevelObject {
         match self {
-            Self::BoardWindow => LevelObject::ShatteredGlass,
+            Self::BoardWindow => LevelObject::Window,
+            Self::PowerGenerator => LevelObject::Generator,
             _ => LevelObject::ShatteredGlass,
         }
     }
@@ -91,6 +109,40 @@ impl TaskTimer {
     }
 }
 
+#[derive(Component)]
+struct TaskWork(Timer);
+
+#[derive(Event)]
+pub struct TaskCompletedEvent {
+    pub task: Task,
+}
+
+#[derive(Event)]
+pub struct TaskPressureEvent {
+    pub task: Task,
+    pub secs: f32,
+}
+
+fn push_task(cmds: &mut Commands, task_list: &mut TaskList, task: Task) {
+    task_list.0.push_back(
+        cmds.spawn((
+            StateScoped(GameState::Playing),
+            SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(Vec2::splat(64.)),
+                    ..default()
+                },
+                transform: Transform::from_translation(task.pos()),
+                ..default()
+            },
+            task,
+            TaskTimer(Timer::from_seconds(60., TimerMode::Once)),
+            TaskWork(Timer::from_seconds(TASK_WORK_SECS, TimerMode::Once)),
+        ))
+        .id(),
+    );
+}
+
 fn spawn_task(mut cmds: Commands, mut task_list: ResMut<TaskList>, task_qry: Query<&Task>) {
     if task_list.0.len() != TaskList::MAX_SIZE {
         let mut task;
@@ -98,21 +150,32 @@ fn spawn_task(mut cmds: Commands, mut task_list: ResMut<TaskList>, task_qry: Que
             task = Task::try_from(rand::thread_rng().gen_range(0..Task::COUNT)).unwrap();
             task_qry.iter().any(|&other_task| other_task == task)
         } {}
-        task_list.0.push_back(
-            cmds.spawn((
-                SpriteBundle {
-                    sprite: Sprite {
-                        custom_size: Some(Vec2::splat(64.)),
-                        ..default()
-                    },
-                    transform: Transform::from_translation(task.pos()),
-                    ..default()
-                },
-                task,
-                TaskTimer(Timer::from_seconds(60., TimerMode::Once)),
-            ))
-            .id(),
-        );
+        push_task(&mut cmds, &mut task_list, task);
+    }
+}
+
+//Zombies attacking the house speed up the matching task, or trigger it if it isn't active
+fn apply_task_pressure(
+    mut cmds: Commands,
+    mut task_pressure_evr: EventReader<TaskPressureEvent>,
+    mut task_qry: Query<(&Task, &mut TaskTimer)>,
+    mut task_list: ResMut<TaskList>,
+) {
+    let mut triggered_tasks = Vec::new();
+    for task_pressure in task_pressure_evr.read() {
+        if let Some((_, mut task_timer)) = task_qry
+            .iter_mut()
+            .find(|(task, _)| **task == task_pressure.task)
+        {
+            task_timer
+                .0
+                .tick(Duration::from_secs_f32(task_pressure.secs));
+        } else if task_list.0.len() != TaskList::MAX_SIZE
+            && !triggered_tasks.contains(&task_pressure.task)
+        {
+            triggered_tasks.push(task_pressure.task);
+            push_task(&mut cmds, &mut task_list, task_pressure.task);
+        }
     }
 }
 
@@ -120,22 +183,70 @@ pub fn update_task_timers(
     mut cmds: Commands,
     mut task_timer_qry: Query<(Entity, &mut TaskTimer), With<Task>>,
     mut task_list: ResMut<TaskList>,
-    mut player_hp_bar: ResMut<PlayerHealthBar>,
+    mut damage_evw: EventWriter<DamageEvent>,
+    player_qry: Query<Entity, With<Player>>,
     time: Res<Time>,
 ) {
     let dt = time.delta();
+    let player_id = player_qry.single();
     for (task_id, mut task_timer) in &mut task_timer_qry {
         task_timer.0.tick(dt);
-        if task_timer.0.just_finished() {
+        if task_timer.0.finished() {
             cmds.entity(task_id).despawn_recursive();
-            task_list.0.pop_front();
-            player_hp_bar.0.pop();
+            task_list.0.retain(|&id| id != task_id);
+            damage_evw.send(DamageEvent {
+                target_id: player_id,
+                half_hearts: 2,
+                source: DamageSource::TaskFailure,
+                knockback: Vec2::ZERO,
+            });
         }
     }
 }
 
-fn mouse_highlight_task(
-    mouse_pos: Res<MousePosition>,
+//Staying on the closest task long enough completes it and frees its place in the task list
+fn work_on_tasks(
+    mut cmds: Commands,
+    mut player_qry: Query<(&mut Player, &Transform, &mut Inventory)>,
+    mut task_qry: Query<(Entity, &Task, &Transform, &mut TaskWork)>,
+    mut task_list: ResMut<TaskList>,
+    mut task_completed_evw: EventWriter<TaskCompletedEvent>,
+    time: Res<Time>,
+) {
+    let (mut player, player_xform, mut inventory) = player_qry.single_mut();
+    if !player.doing_task() {
+        return;
+    }
+    let player_pos = player_xform.translation.truncate();
+    let Some((task_id, &task, _, mut task_work)) = task_qry
+        .iter_mut()
+        .filter(|(_, _, task_xform, _)| {
+            task_xform.translation.truncate().distance(player_pos) <= AVG_TILE_DIMENSION
+        })
+        .min_by(|(_, _, task_a_xform, _), (_, _, task_b_xform, _)| {
+            (task_a_xform.translation.truncate().distance(player_pos))
+                .total_cmp(&task_b_xform.translation.truncate().distance(player_pos))
+        })
+    else {
+        return;
+    };
+    if !task_work.0.tick(time.delta()).finished() {
+        return;
+    }
+    match task.required_item() {
+        Some(item) if task.uses_up_required_item() => {
+            inventory.take(item, 1);
+        }
+        _ => (),
+    }
+    cmds.entity(task_id).despawn_recursive();
+    task_list.0.retain(|&id| id != task_id);
+    player.stop_task();
+    task_completed_evw.send(TaskCompletedEvent { task });
+}
+
+fn aim_highlight_task(
+    aim_pos: Res<AimPosition>,
     mut task_qry: Query<(&mut Sprite, &Transform), With<Task>>,
     player_qry: Query<&Transform, With<Player>>,
 ) {
@@ -144,7 +255,7 @@ fn mouse_highlight_task(
         .iter_mut()
         .map(|(task_sprite, task_xform)| (task_sprite, task_xform.translation.truncate()))
         .find(|(_, task_pos)| {
-            (mouse_pos.as_vec().distance(*task_pos) <= AVG_TILE_DIMENSION)
+            (aim_pos.as_vec().distance(*task_pos) <= AVG_TILE_DIMENSION)
                 && (player_pos.distance(*task_pos) <= AVG_TILE_DIMENSION)
         })
         .map(|(task_sprite, _)| task_sprite)
@@ -159,18 +270,29 @@ fn mouse_highlight_task(
 }
 
 pub fn task_plugin(app: &mut App) {
-    app.add_systems(
-        Update,
-        (update_task_timers, mouse_highlight_task).run_if(in_state(GameState::Playing)),
-    )
-    .add_systems(
-        OnEnter(GameState::Playing),
-        (
-            |mut cmds: Commands| {
-                cmds.insert_resource(TaskList::new());
-            },
-            spawn_task,
+    app.add_event::<TaskPressureEvent>()
+        .add_event::<TaskCompletedEvent>()
+        .add_systems(
+            Update,
+            (
+                apply_task_pressure.before(update_task_timers),
+                update_task_timers,
+                work_on_tasks
+                    .after(player::player_task_input)
+                    .before(update_task_timers)
+                    .run_if(player::is_alive),
+                aim_highlight_task,
+            )
+                .run_if(in_state(GameState::Playing)),
         )
-            .chain(),
-    );
+        .add_systems(
+            OnEnter(GameState::Playing),
+            (
+                |mut cmds: Commands| {
+                    cmds.insert_resource(TaskList::new());
+                },
+                spawn_task,
+            )
+                .chain(),
+        );
 }
@@ -0,0 +1,180 @@
+use {
+    super::{
+        asset_owner::TextureAtlasOwner,
+        boss::BossTelegraph,
+        game_state::GameState,
+        level::LevelObject,
+        primary_camera::PrimaryCamera,
+        tile::{Tile, AVG_TILE_DIMENSION},
+        zombie::{self, Zombie, ZombiePool, ZombiePopulation},
+    },
+    bevy::prelude::*,
+    rand::Rng,
+};
+
+const TELEGRAPH_SECS: f32 = 2.;
+const EMERGE_SECS: f32 = 1.;
+const MIN_SPAWN_DISTANCE: f32 = AVG_TILE_DIMENSION * 5.;
+const OFFSCREEN_MARGIN: f32 = AVG_TILE_DIMENSION;
+
+#[derive(Component)]
+pub struct SpawnTelegraph(Timer);
+
+#[derive(Clone, Copy)]
+enum EntryKind {
+    RiseFromDirt,
+    ClimbFence { from: Vec2, to: Vec2 },
+}
+
+#[derive(Component)]
+pub struct Emerging {
+    kind: EntryKind,
+    timer: Timer,
+}
+
+//Prefers spawn points that are off screen and far from the player, falling back to the farthest one
+pub fn pick_spawn_point(
+    spawn_points: &[Vec2],
+    player_pos: Vec2,
+    visible_area: Rect,
+    thread_rng: &mut impl Rng,
+) -> Option<Vec2> {
+    let visible_area = visible_area.inflate(OFFSCREEN_MARGIN);
+    let hidden_spawn_points = spawn_points
+        .iter()
+        .copied()
+        .filter(|&spawn_pos| {
+            !visible_area.contains(spawn_pos)
+                && spawn_pos.distance(player_pos) >= MIN_SPAWN_DISTANCE
+        })
+        .collect::<Vec<_>>();
+    if hidden_spawn_points.is_empty() {
+        return spawn_points
+            .iter()
+            .copied()
+            .max_by(|spawn_a_pos, spawn_b_pos| {
+                spawn_a_pos
+                    .distance(player_pos)
+                    .total_cmp(&spawn_b_pos.distance(player_pos))
+            });
+    }
+    Some(hidden_spawn_points[thread_rng.gen_range(0..hidden_spawn_points.len())])
+}
+
+pub fn visible_area(
+    primary_cam_qry: &Query<(&OrthographicProjection, &Transform), With<PrimaryCamera>>,
+) -> Rect {
+    let (primary_cam_proj, primary_cam_xform) = primary_cam_qry.single();
+    let cam_pos = primary_cam_xform.translation.truncate();
+    Rect::from_corners(
+        cam_pos + primary_cam_proj.area.min,
+        cam_pos + primary_cam_proj.area.max,
+    )
+}
+
+//A telegraph only goes up once it holds a slot under the population cap, so its zombie can always
+//emerge when it finishes
+pub fn spawn_telegraph(
+    cmds: &mut Commands,
+    zombie_pool: &mut ZombiePool,
+    zombie_population: &ZombiePopulation,
+    telegraph_pos: Vec2,
+    tile_tex_atlas: &Res<TextureAtlasOwner<Tile>>,
+) -> Option<Entity> {
+    if !zombie_pool.reserve(zombie_population) {
+        return None;
+    }
+    Some(
+        cmds.spawn((
+            StateScoped(GameState::Playing),
+            SpawnTelegraph(Timer::from_seconds(TELEGRAPH_SECS, TimerMode::Once)),
+            SpriteBundle {
+                transform: Transform::from_translation(telegraph_pos.extend(7.)),
+                texture: tile_tex_atlas.texture(),
+                ..default()
+            },
+            TextureAtlas {
+                layout: tile_tex_atlas.layout(),
+                index: LevelObject::Bush.id(),
+            },
+        ))
+        .id(),
+    )
+}
+
+fn rustle_telegraphs(
+    mut cmds: Commands,
+    mut telegraph_qry: Query<(
+        Entity,
+        &mut SpawnTelegraph,
+        &mut Transform,
+        Option<&BossTelegraph>,
+    )>,
+    mut zombie_pool: ResMut<ZombiePool>,
+    zombie_tex_atlas: Res<TextureAtlasOwner<Zombie>>,
+    time: Res<Time>,
+) {
+    let mut thread_rng = rand::thread_rng();
+    for (telegraph_id, mut telegraph, mut telegraph_xform, boss_telegraph) in &mut telegraph_qry {
+        if !telegraph.0.tick(time.delta()).finished() {
+            let rustle = (telegraph.0.elapsed_secs() * 30.).sin() * telegraph.0.fraction() * 0.2;
+            telegraph_xform.rotation = Quat::from_rotation_z(rustle);
+            continue;
+        }
+        let spawn_pos = telegraph_xform.translation.truncate();
+        let zombie_id = zombie::spawn_reserved_zombie(
+            &mut cmds,
+            &mut zombie_pool,
+            spawn_pos,
+            &zombie_tex_atlas,
+        );
+        cmds.entity(telegraph_id).despawn_recursive();
+
+        let kind = if thread_rng.gen::<bool>() {
+            EntryKind::RiseFromDirt
+        } else {
+            EntryKind::ClimbFence {
+                from: spawn_pos + spawn_pos.normalize_or_zero() * AVG_TILE_DIMENSION / 2.,
+                to: spawn_pos,
+            }
+        };
+        cmds.entity(zombie_id).insert(Emerging {
+            kind,
+            timer: Timer::from_seconds(EMERGE_SECS, TimerMode::Once),
+        });
+        if let Some(boss_telegraph) = boss_telegraph {
+            cmds.entity(zombie_id).insert(boss_telegraph.boss_bundle());
+        }
+    }
+}
+
+fn emerge_zombies(
+    mut cmds: Commands,
+    mut emerging_qry: Query<(Entity, &mut Emerging, &mut Transform)>,
+    time: Res<Time>,
+) {
+    for (emerging_id, mut emerging, mut emerging_xform) in &mut emerging_qry {
+        let t = emerging.timer.tick(time.delta()).fraction();
+        match emerging.kind {
+            EntryKind::RiseFromDirt => {
+                emerging_xform.scale = Vec3::splat(0.2 + 0.8 * t);
+            }
+            EntryKind::ClimbFence { from, to } => {
+                let hop = 1. + (t * std::f32::consts::PI).sin() * 0.3;
+                emerging_xform.scale = Vec3::new(hop, hop, 1.);
+                emerging_xform.translation = from.lerp(to, t).extend(emerging_xform.translation.z);
+            }
+        }
+        if emerging.timer.finished() {
+            emerging_xform.scale = Vec3::ONE;
+            cmds.entity(emerging_id).remove::<Emerging>();
+        }
+    }
+}
+
+pub fn telegraph_plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (rustle_telegraphs, emerge_zombies).run_if(in_state(GameState::Playing)),
+    );
+}
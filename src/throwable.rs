@@ -0,0 +1,384 @@
+use {
+    super::{
+        aim::AimPosition,
+        asset_owner::TextureAtlasOwner,
+        game_state::GameState,
+        health::{DamageEvent, DamageSource},
+        inventory::{Inventory, Item},
+        level::LevelObject,
+        perception::{self, NoiseEvent},
+        player::{self, Player, PlayerAction},
+        tile::{Tile, Wall, AVG_TILE_DIMENSION},
+        zombie::Zombie,
+    },
+    bevy::prelude::*,
+    bevy_rapier2d::prelude::*,
+    leafwing_input_manager::prelude::*,
+    std::f32::consts::PI,
+};
+
+const THROW_RANGE: f32 = AVG_TILE_DIMENSION * 7.;
+const THROW_SPEED: f32 = AVG_TILE_DIMENSION * 8.;
+const MIN_THROW_SECS: f32 = 0.25;
+const ARC_HEIGHT: f32 = AVG_TILE_DIMENSION * 0.75;
+const THROWN_RADIUS: f32 = 8.;
+const THROWN_SCALE: f32 = 0.5;
+const THROWN_Z: f32 = 12.;
+const LANDED_Z: f32 = 2.;
+const NOISE_MAKER_SECS: f32 = 8.;
+const NOISE_MAKER_BEEP_SECS: f32 = 0.5;
+const NOISE_MAKER_RADIUS: f32 = AVG_TILE_DIMENSION * 10.;
+const FIRE_RADIUS: f32 = AVG_TILE_DIMENSION * 1.25;
+const FIRE_SECS: f32 = 6.;
+const FIRE_TICK_SECS: f32 = 0.5;
+const FIRE_DAMAGE_PER_TICK: f32 = 0.5;
+const FIRE_NOISE_RADIUS: f32 = AVG_TILE_DIMENSION * 4.;
+const FIRE_COLOR: Color = Color::srgb(1., 0.6, 0.3);
+const FLASHBANG_RADIUS: f32 = AVG_TILE_DIMENSION * 4.;
+const FLASHBANG_STUN_SECS: f32 = 4.;
+const FLASHBANG_NOISE_RADIUS: f32 = AVG_TILE_DIMENSION * 12.;
+const STUNNED_COLOR: Color = Color::srgb(0.75, 0.85, 1.);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Throwable {
+    NoiseMaker,
+    Molotov,
+    Flashbang,
+}
+
+impl Throwable {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::NoiseMaker => "Noise maker",
+            Self::Molotov => "Molotov",
+            Self::Flashbang => "Flashbang",
+        }
+    }
+
+    fn level_object(&self) -> LevelObject {
+        match self {
+            Self::NoiseMaker => LevelObject::NoiseMaker,
+            Self::Molotov => LevelObject::Molotov,
+            Self::Flashbang => LevelObject::Flashbang,
+        }
+    }
+}
+
+//Throws travel in a straight line along the ground, and the arc is only drawn by lifting and
+//growing the sprite towards the middle of the flight
+#[derive(Component)]
+struct Thrown {
+    throwable: Throwable,
+    from: Vec2,
+    to: Vec2,
+    ground_pos: Vec2,
+    flight: Timer,
+}
+
+#[derive(Event)]
+struct ThrowableLandedEvent {
+    throwable: Throwable,
+    pos: Vec2,
+}
+
+#[derive(Component)]
+struct NoiseMaker {
+    beep: Timer,
+    lifetime: Timer,
+}
+
+#[derive(Component)]
+struct Fire {
+    tick: Timer,
+    lifetime: Timer,
+}
+
+#[derive(Component)]
+pub struct Stunned(Timer);
+
+#[derive(Event)]
+pub struct FireDamageEvent {
+    pub target_id: Entity,
+    pub damage: f32,
+}
+
+fn spawn_landed_sprite(
+    cmds: &mut Commands,
+    pos: Vec2,
+    lvl_obj: LevelObject,
+    sprite: Sprite,
+    tile_tex_atlas: &Res<TextureAtlasOwner<Tile>>,
+) -> Entity {
+    cmds.spawn((
+        StateScoped(GameState::Playing),
+        SpriteBundle {
+            sprite,
+            transform: Transform::from_translation(pos.extend(LANDED_Z)),
+            texture: tile_tex_atlas.texture(),
+            ..default()
+        },
+        TextureAtlas {
+            layout: tile_tex_atlas.layout(),
+            index: lvl_obj.id(),
+        },
+    ))
+    .id()
+}
+
+//Firing with a throwable selected throws one towards the aim, up to the throw range
+fn throw_selected(
+    mut cmds: Commands,
+    mut player_qry: Query<(
+        &Player,
+        &Transform,
+        &mut Inventory,
+        &ActionState<PlayerAction>,
+    )>,
+    aim_pos: Res<AimPosition>,
+    tile_tex_atlas: Res<TextureAtlasOwner<Tile>>,
+) {
+    let Ok((player, player_xform, mut inventory, player_in)) = player_qry.get_single_mut() else {
+        return;
+    };
+    let Some(Item::Throwable(throwable)) = inventory.selected_item() else {
+        return;
+    };
+    if player.doing_task() || !player_in.just_pressed(&PlayerAction::Fire) {
+        return;
+    }
+    inventory.take(Item::Throwable(throwable), 1);
+    let from = player_xform.translation.truncate();
+    let to = from + (aim_pos.as_vec() - from).clamp_length_max(THROW_RANGE);
+    cmds.spawn((
+        Thrown {
+            throwable,
+            from,
+            to,
+            ground_pos: from,
+            flight: Timer::from_seconds(
+                (from.distance(to) / THROW_SPEED).max(MIN_THROW_SECS),
+                TimerMode::Once,
+            ),
+        },
+        StateScoped(GameState::Playing),
+        SpriteBundle {
+            transform: Transform::from_translation(from.extend(THROWN_Z))
+                .with_scale(Vec3::splat(THROWN_SCALE)),
+            texture: tile_tex_atlas.texture(),
+            ..default()
+        },
+        TextureAtlas {
+            layout: tile_tex_atlas.layout(),
+            index: throwable.level_object().id(),
+        },
+    ));
+}
+
+//Each step is ray cast against the walls so a throw into a wall drops just in front of it
+fn fly_thrown(
+    mut cmds: Commands,
+    mut thrown_qry: Query<(Entity, &mut Thrown, &mut Transform)>,
+    wall_qry: Query<(), With<Wall>>,
+    rapier_ctx: Res<RapierContext>,
+    mut landed_evw: EventWriter<ThrowableLandedEvent>,
+    time: Res<Time>,
+) {
+    for (thrown_id, mut thrown, mut thrown_xform) in &mut thrown_qry {
+        let t = thrown.flight.tick(time.delta()).fraction();
+        let next_pos = thrown.from.lerp(thrown.to, t);
+        let step = next_pos - thrown.ground_pos;
+        let wall_hit = Dir2::new(step).ok().and_then(|step_dir| {
+            rapier_ctx.cast_ray_and_get_normal(
+                thrown.ground_pos,
+                *step_dir,
+                step.length(),
+                true,
+                QueryFilter::new().predicate(&|collider_id| wall_qry.contains(collider_id)),
+            )
+        });
+        let hit_wall = wall_hit.is_some();
+        thrown.ground_pos =
+            wall_hit.map_or(next_pos, |(_, hit)| hit.point + hit.normal * THROWN_RADIUS);
+        if hit_wall || thrown.flight.finished() {
+            cmds.entity(thrown_id).despawn_recursive();
+            landed_evw.send(ThrowableLandedEvent {
+                throwable: thrown.throwable,
+                pos: thrown.ground_pos,
+            });
+            continue;
+        }
+        let height = (t * PI).sin() * ARC_HEIGHT;
+        thrown_xform.translation = (thrown.ground_pos + Vec2::Y * height).extend(THROWN_Z);
+        thrown_xform.scale = Vec3::splat(THROWN_SCALE * (1. + height / ARC_HEIGHT * 0.5));
+    }
+}
+
+//Flashbangs only stun zombies with a clear line to the bang, but every landing is heard
+fn land_throwables(
+    mut cmds: Commands,
+    mut landed_evr: EventReader<ThrowableLandedEvent>,
+    mut zombie_qry: Query<(Entity, &Transform, &mut Sprite), With<Zombie>>,
+    wall_qry: Query<(), With<Wall>>,
+    rapier_ctx: Res<RapierContext>,
+    tile_tex_atlas: Res<TextureAtlasOwner<Tile>>,
+    mut noise_evw: EventWriter<NoiseEvent>,
+) {
+    for landed in landed_evr.read() {
+        let noise_radius = match landed.throwable {
+            Throwable::NoiseMaker => {
+                let noise_maker_id = spawn_landed_sprite(
+                    &mut cmds,
+                    landed.pos,
+                    LevelObject::NoiseMaker,
+                    Sprite {
+                        custom_size: Some(Vec2::splat(AVG_TILE_DIMENSION * THROWN_SCALE)),
+                        ..default()
+                    },
+                    &tile_tex_atlas,
+                );
+                cmds.entity(noise_maker_id).insert(NoiseMaker {
+                    beep: Timer::from_seconds(NOISE_MAKER_BEEP_SECS, TimerMode::Repeating),
+                    lifetime: Timer::from_seconds(NOISE_MAKER_SECS, TimerMode::Once),
+                });
+                NOISE_MAKER_RADIUS
+            }
+            Throwable::Molotov => {
+                let fire_id = spawn_landed_sprite(
+                    &mut cmds,
+                    landed.pos,
+                    LevelObject::Fire,
+                    Sprite {
+                        color: FIRE_COLOR,
+                        custom_size: Some(Vec2::splat(FIRE_RADIUS * 2.)),
+                        ..default()
+                    },
+                    &tile_tex_atlas,
+                );
+                cmds.entity(fire_id).insert(Fire {
+                    tick: Timer::from_seconds(FIRE_TICK_SECS, TimerMode::Repeating),
+                    lifetime: Timer::from_seconds(FIRE_SECS, TimerMode::Once),
+                });
+                FIRE_NOISE_RADIUS
+            }
+            Throwable::Flashbang => {
+                for (zombie_id, zombie_xform, mut zombie_sprite) in &mut zombie_qry {
+                    let zombie_pos = zombie_xform.translation.truncate();
+                    if zombie_pos.distance(landed.pos) > FLASHBANG_RADIUS
+                        || !perception::can_see(&rapier_ctx, &wall_qry, landed.pos, zombie_pos)
+                    {
+                        continue;
+                    }
+                    zombie_sprite.color = STUNNED_COLOR;
+                    cmds.entity(zombie_id).insert(Stunned(Timer::from_seconds(
+                        FLASHBANG_STUN_SECS,
+                        TimerMode::Once,
+                    )));
+                }
+                FLASHBANG_NOISE_RADIUS
+            }
+        };
+        noise_evw.send(NoiseEvent {
+            pos: landed.pos,
+            radius: noise_radius,
+        });
+    }
+}
+
+//Every beep draws in the zombies that hear it, pulsing the sprite so the player can see it going
+fn beep_noise_makers(
+    mut cmds: Commands,
+    mut noise_maker_qry: Query<(Entity, &mut NoiseMaker, &mut Transform)>,
+    mut noise_evw: EventWriter<NoiseEvent>,
+    time: Res<Time>,
+) {
+    for (noise_maker_id, mut noise_maker, mut noise_maker_xform) in &mut noise_maker_qry {
+        if noise_maker.lifetime.tick(time.delta()).finished() {
+            cmds.entity(noise_maker_id).despawn_recursive();
+            continue;
+        }
+        if noise_maker.beep.tick(time.delta()).just_finished() {
+            noise_evw.send(NoiseEvent {
+                pos: noise_maker_xform.translation.truncate(),
+                radius: NOISE_MAKER_RADIUS,
+            });
+        }
+        let pulse = 1. - noise_maker.beep.fraction();
+        noise_maker_xform.scale = Vec3::splat(1. + pulse * 0.3);
+    }
+}
+
+//Fire burns everything standing in it, including the player, and fades out over its last second
+fn burn_fires(
+    mut cmds: Commands,
+    mut fire_qry: Query<(Entity, &mut Fire, &Transform, &mut Sprite)>,
+    zombie_qry: Query<(Entity, &Transform), With<Zombie>>,
+    player_qry: Query<(Entity, &Transform), With<Player>>,
+    mut fire_damage_evw: EventWriter<FireDamageEvent>,
+    mut damage_evw: EventWriter<DamageEvent>,
+    time: Res<Time>,
+) {
+    for (fire_id, mut fire, fire_xform, mut fire_sprite) in &mut fire_qry {
+        if fire.lifetime.tick(time.delta()).finished() {
+            cmds.entity(fire_id).despawn_recursive();
+            continue;
+        }
+        fire_sprite
+            .color
+            .set_alpha(fire.lifetime.remaining_secs().min(1.));
+        if !fire.tick.tick(time.delta()).just_finished() {
+            continue;
+        }
+        let fire_pos = fire_xform.translation.truncate();
+        let in_fire =
+            |xform: &Transform| xform.translation.truncate().distance(fire_pos) <= FIRE_RADIUS;
+        for (zombie_id, zombie_xform) in &zombie_qry {
+            if in_fire(zombie_xform) {
+                fire_damage_evw.send(FireDamageEvent {
+                    target_id: zombie_id,
+                    damage: FIRE_DAMAGE_PER_TICK,
+                });
+            }
+        }
+        for (player_id, player_xform) in &player_qry {
+            if in_fire(player_xform) {
+                damage_evw.send(DamageEvent {
+                    target_id: player_id,
+                    half_hearts: 1,
+                    source: DamageSource::Hazard,
+                    knockback: Vec2::ZERO,
+                });
+            }
+        }
+    }
+}
+
+fn recover_from_stuns(
+    mut cmds: Commands,
+    mut stunned_qry: Query<(Entity, &mut Stunned, &mut Sprite)>,
+    time: Res<Time>,
+) {
+    for (stunned_id, mut stunned, mut stunned_sprite) in &mut stunned_qry {
+        if stunned.0.tick(time.delta()).finished() {
+            stunned_sprite.color = Color::WHITE;
+            cmds.entity(stunned_id).remove::<Stunned>();
+        }
+    }
+}
+
+pub fn throwable_plugin(app: &mut App) {
+    app.add_event::<ThrowableLandedEvent>()
+        .add_event::<FireDamageEvent>()
+        .add_systems(
+            Update,
+            (
+                throw_selected
+                    .after(player::player_task_input)
+                    .run_if(player::can_act),
+                (fly_thrown, land_throwables).chain(),
+                beep_noise_makers,
+                burn_fires,
+                recover_from_stuns,
+            )
+                .run_if(in_state(GameState::Playing)),
+        );
+}
@@ -1,5 +1,10 @@
 use {
-    super::{asset_owner::TextureAtlasOwner, level::LevelObject, GameState},
+    super::{
+        asset_owner::TextureAtlasOwner,
+        collision::{self, WALL_GROUP},
+        level::LevelObject,
+        GameState,
+    },
     bevy::prelude::*,
     bevy_rapier2d::prelude::*,
 };
@@ -9,10 +14,56 @@ pub const AVG_TILE_DIMENSION: f32 = (TILE_SIZE.x + TILE_SIZE.y) / 2.;
 const HALF_TILE_SIZE: Vec2 = Vec2::new(TILE_SIZE.x / 2., TILE_SIZE.y / 2.);
 const WALL_THICKNESS: f32 = 15.;
 const HALF_WALL_THICKNESS: f32 = WALL_THICKNESS / 2.;
+pub const LEFT_WALL: u8 = 1;
+pub const RIGHT_WALL: u8 = 2;
+pub const TOP_WALL: u8 = 4;
+pub const BOTTOM_WALL: u8 = 8;
 
 #[derive(Component)]
 pub struct Tile;
 
+#[derive(Component)]
+pub struct Wall;
+
+//The edges of a tile that get a wall collider, as a set of the *_WALL flags
+pub fn wall_sides(tile_lvl_obj: LevelObject) -> u8 {
+    let mut wall_sides = 0;
+    if matches!(
+        tile_lvl_obj,
+        LevelObject::LeftWall
+            | LevelObject::BottomLeftWall
+            | LevelObject::TopLeftWall
+            | LevelObject::UShapedWall
+    ) {
+        wall_sides |= LEFT_WALL;
+    }
+    if matches!(
+        tile_lvl_obj,
+        LevelObject::RightWall
+            | LevelObject::BottomRightWall
+            | LevelObject::TopRightWall
+            | LevelObject::UShapedWall
+    ) {
+        wall_sides |= RIGHT_WALL;
+    }
+    if matches!(
+        tile_lvl_obj,
+        LevelObject::TopWall | LevelObject::TopLeftWall | LevelObject::TopRightWall
+    ) {
+        wall_sides |= TOP_WALL;
+    }
+    if matches!(
+        tile_lvl_obj,
+        LevelObject::BottomWall
+            | LevelObject::BottomLeftWall
+            | LevelObject::BottomRightWall
+            | LevelObject::UShapedWall
+    ) {
+        wall_sides |= BOTTOM_WALL;
+    }
+    wall_sides
+}
+
 pub fn spawn_tile(
     cmds: &mut Commands,
     tile_pos: Vec2,
@@ -35,64 +86,52 @@ pub fn spawn_tile(
         },
     ))
     .with_children(|parent| {
-        if matches!(
-            tile_lvl_obj,
-            LevelObject::LeftWall
-                | LevelObject::BottomLeftWall
-                | LevelObject::TopLeftWall
-                | LevelObject::UShapedWall
-        ) {
+        let wall_sides = wall_sides(tile_lvl_obj);
+        if wall_sides & LEFT_WALL != 0 {
             parent.spawn((
                 TransformBundle::from_transform(Transform::from_xyz(
                     -HALF_TILE_SIZE.x - HALF_WALL_THICKNESS + WALL_THICKNESS,
                     0.,
                     0.,
                 )),
+                Wall,
+                collision::groups(WALL_GROUP),
                 Collider::cuboid(HALF_WALL_THICKNESS, HALF_TILE_SIZE.y),
             ));
         }
-        if matches!(
-            tile_lvl_obj,
-            LevelObject::RightWall
-                | LevelObject::BottomRightWall
-                | LevelObject::TopRightWall
-                | LevelObject::UShapedWall
-        ) {
+        if wall_sides & RIGHT_WALL != 0 {
             parent.spawn((
                 TransformBundle::from_transform(Transform::from_xyz(
                     HALF_TILE_SIZE.x + HALF_WALL_THICKNESS - WALL_THICKNESS,
                     0.,
                     0.,
                 )),
+                Wall,
+                collision::groups(WALL_GROUP),
                 Collider::cuboid(HALF_WALL_THICKNESS, HALF_TILE_SIZE.y),
             ));
         }
-        if matches!(
-            tile_lvl_obj,
-            LevelObject::TopWall | LevelObject::TopLeftWall | LevelObject::TopRightWall
-        ) {
+        if wall_sides & TOP_WALL != 0 {
             parent.spawn((
                 TransformBundle::from_transform(Transform::from_xyz(
                     0.,
                     HALF_TILE_SIZE.y + HALF_WALL_THICKNESS - WALL_THICKNESS,
                     0.,
                 )),
+                Wall,
+                collision::groups(WALL_GROUP),
                 Collider::cuboid(HALF_TILE_SIZE.x, HALF_WALL_THICKNESS),
             ));
         }
-        if matches!(
-            tile_lvl_obj,
-            LevelObject::BottomWall
-                | LevelObject::BottomLeftWall
-                | LevelObject::BottomRightWall
-                | LevelObject::UShapedWall
-        ) {
+        if wall_sides & BOTTOM_WALL != 0 {
             parent.spawn((
                 TransformBundle::from_transform(Transform::from_xyz(
                     0.,
                     -HALF_TILE_SIZE.y - HALF_WALL_THICKNESS + WALL_THICKNESS,
                     0.,
                 )),
+                Wall,
+                collision::groups(WALL_GROUP),
                 Collider::cuboid(HALF_TILE_SIZE.x, HALF_WALL_THICKNESS),
             ));
         }
@@ -103,6 +142,8 @@ pub fn spawn_tile(
                     HALF_TILE_SIZE.y + HALF_WALL_THICKNESS - WALL_THICKNESS,
                     0.,
                 )),
+                Wall,
+                collision::groups(WALL_GROUP),
                 Collider::cuboid(HALF_WALL_THICKNESS, HALF_WALL_THICKNESS),
             ));
         }
@@ -113,6 +154,8 @@ pub fn spawn_tile(
                     HALF_TILE_SIZE.y + HALF_WALL_THICKNESS - WALL_THICKNESS,
                     0.,
                 )),
+                Wall,
+                collision::groups(WALL_GROUP),
                 Collider::cuboid(HALF_WALL_THICKNESS, HALF_WALL_THICKNESS),
             ));
         }
@@ -123,6 +166,8 @@ pub fn spawn_tile(
                     -HALF_TILE_SIZE.y - HALF_WALL_THICKNESS + WALL_THICKNESS,
                     0.,
                 )),
+                Wall,
+                collision::groups(WALL_GROUP),
                 Collider::cuboid(HALF_WALL_THICKNESS, HALF_WALL_THICKNESS),
             ));
         }
@@ -133,6 +178,8 @@ pub fn spawn_tile(
                     -HALF_TILE_SIZE.y - HALF_WALL_THICKNESS + WALL_THICKNESS,
                     0.,
                 )),
+                Wall,
+                collision::groups(WALL_GROUP),
                 Collider::cuboid(HALF_WALL_THICKNESS, HALF_WALL_THICKNESS),
             ));
         }
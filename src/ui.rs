@@ -2,15 +2,26 @@ use {
     super::{
         asset_owner::FontOwner,
         asset_owner::TextureAtlasOwner,
+        boss::Boss,
+        combat::EquippedWeapon,
+        health::PlayerHealthBar,
+        inventory::{Inventory, Item},
         level,
-        player::PlayerHealthBar,
+        player::Player,
+        stamina::Stamina,
         task::{self, Task, TaskList, TaskTimer},
+        zombie::ZombieHealth,
         GameState,
     },
     crate::RESOLUTION,
     bevy::prelude::*,
 };
 
+pub const BUTTON_COLOR: Color = Color::srgba(0.47, 0.47, 0.47, 0.6);
+pub const HOVERED_BUTTON_COLOR: Color = Color::srgba(0.67, 0.67, 0.67, 0.8);
+const HOTBAR_SLOT_COLOR: Color = Color::srgba(0.47, 0.47, 0.47, 0.6);
+const HOTBAR_SELECTED_SLOT_COLOR: Color = Color::srgba(0.9, 0.75, 0.2, 0.8);
+
 #[derive(Component)]
 struct Ui;
 
@@ -20,6 +31,27 @@ struct TaskUi;
 #[derive(Component)]
 struct HealthUi;
 
+#[derive(Component)]
+struct StaminaUi;
+
+#[derive(Component)]
+struct AmmoUi;
+
+#[derive(Component)]
+struct HotbarSlotUi(usize);
+
+#[derive(Component)]
+struct HotbarItemUi(usize);
+
+#[derive(Component)]
+struct BossUi;
+
+#[derive(Component)]
+struct BossNameUi;
+
+#[derive(Component)]
+struct BossHealthUi;
+
 fn spawn_hud(mut cmds: Commands, health_bar_tex_atlas: Res<TextureAtlasOwner<HealthUi>>) {
     cmds.spawn((
         Ui,
@@ -116,6 +148,140 @@ fn spawn_hud(mut cmds: Commands, health_bar_tex_atlas: Res<TextureAtlasOwner<Hea
                     }
                 });
             });
+        screen
+            .spawn(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Percent(9.),
+                    left: Val::Percent(2.),
+                    width: Val::Percent(20.),
+                    height: Val::Percent(1.5),
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::Srgba(Srgba::rgba_u8(120, 120, 120, 150))),
+                ..default()
+            })
+            .with_children(|stamina_bar| {
+                stamina_bar.spawn((
+                    StaminaUi,
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Percent(100.),
+                            height: Val::Percent(100.),
+                            ..default()
+                        },
+                        background_color: BackgroundColor(Color::Srgba(Srgba::rgb_u8(90, 200, 90))),
+                        ..default()
+                    },
+                ));
+            });
+        screen.spawn((
+            AmmoUi,
+            TextBundle::from_section("", TextStyle::default()).with_style(Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Percent(5.),
+                right: Val::Percent(5.),
+                ..default()
+            }),
+        ));
+        screen
+            .spawn(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Percent(15.),
+                    left: Val::Percent(25.),
+                    width: Val::Percent(50.),
+                    height: Val::Percent(8.),
+                    justify_content: JustifyContent::SpaceEvenly,
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|hotbar| {
+                for i in 0..Inventory::SIZE {
+                    hotbar
+                        .spawn((
+                            HotbarSlotUi(i),
+                            NodeBundle {
+                                style: Style {
+                                    width: Val::Percent(100. / (Inventory::SIZE + 1) as f32),
+                                    height: Val::Percent(100.),
+                                    justify_content: JustifyContent::Center,
+                                    align_items: AlignItems::Center,
+                                    ..default()
+                                },
+                                border_radius: BorderRadius::all(Val::Percent(15.)),
+                                background_color: BackgroundColor(HOTBAR_SLOT_COLOR),
+                                ..default()
+                            },
+                        ))
+                        .with_children(|slot| {
+                            slot.spawn((
+                                HotbarItemUi(i),
+                                TextBundle::from_section(
+                                    "",
+                                    TextStyle {
+                                        font_size: 14.,
+                                        ..default()
+                                    },
+                                )
+                                .with_text_justify(JustifyText::Center),
+                            ));
+                        });
+                }
+            });
+        screen
+            .spawn((
+                BossUi,
+                NodeBundle {
+                    style: Style {
+                        display: Display::None,
+                        position_type: PositionType::Absolute,
+                        bottom: Val::Percent(5.),
+                        left: Val::Percent(20.),
+                        width: Val::Percent(60.),
+                        height: Val::Percent(8.),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                },
+            ))
+            .with_children(|boss_bar| {
+                boss_bar.spawn((
+                    BossNameUi,
+                    TextBundle::from_section("", TextStyle::default()),
+                ));
+                boss_bar
+                    .spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Percent(100.),
+                            height: Val::Percent(40.),
+                            ..default()
+                        },
+                        background_color: BackgroundColor(Color::Srgba(Srgba::rgba_u8(
+                            120, 120, 120, 150,
+                        ))),
+                        ..default()
+                    })
+                    .with_children(|boss_health| {
+                        boss_health.spawn((
+                            BossHealthUi,
+                            NodeBundle {
+                                style: Style {
+                                    width: Val::Percent(100.),
+                                    height: Val::Percent(100.),
+                                    ..default()
+                                },
+                                background_color: BackgroundColor(Color::Srgba(Srgba::rgb_u8(
+                                    170, 20, 20,
+                                ))),
+                                ..default()
+                            },
+                        ));
+                    });
+            });
     });
 }
 
@@ -123,28 +289,105 @@ fn update_hud(
     task_list: Res<TaskList>,
     task_qry: Query<(&Task, &TaskTimer)>,
     mut task_ui_qry: Query<&mut Text, With<TaskUi>>,
-    player_hp_bar: Res<PlayerHealthBar>,
+    player_qry: Query<(&PlayerHealthBar, &Inventory), With<Player>>,
     mut hp_ui_qry: Query<&mut TextureAtlas, With<HealthUi>>,
 ) {
+    let Ok((player_hp_bar, inventory)) = player_qry.get_single() else {
+        return;
+    };
     for (i, mut task_ui) in task_ui_qry.iter_mut().enumerate() {
         task_ui.sections[0] = task_list
             .get(i)
             .and_then(|&task_id| task_qry.get(task_id).ok())
             .map(|(task, task_timer)| {
-                format!("{}! {:.0} s", task.name(), task_timer.remaining_secs())
+                let missing_item = task
+                    .required_item()
+                    .filter(|&item| !inventory.contains(item))
+                    .map(|item| format!(" (needs {})", item.name().to_lowercase()))
+                    .unwrap_or_default();
+                format!(
+                    "{}! {:.0} s{missing_item}",
+                    task.name(),
+                    task_timer.remaining_secs()
+                )
             })
             .unwrap_or(String::new())
             .into();
     }
     for (i, mut hp_ui) in hp_ui_qry.iter_mut().enumerate() {
-        hp_ui.index = match player_hp_bar.0.get(i) {
-            Some(2) => 0,
-            Some(1) => 1,
+        hp_ui.index = match player_hp_bar.heart(i) {
+            2 => 0,
+            1 => 1,
             _ => 2,
         }
     }
 }
 
+fn update_stamina_hud(
+    stamina_qry: Query<&Stamina, With<Player>>,
+    mut stamina_ui_qry: Query<&mut Style, With<StaminaUi>>,
+) {
+    stamina_ui_qry.single_mut().width = Val::Percent(stamina_qry.single().fraction() * 100.);
+}
+
+fn update_ammo_hud(
+    weapon_qry: Query<(Option<&EquippedWeapon>, &Inventory), With<Player>>,
+    mut ammo_ui_qry: Query<&mut Text, With<AmmoUi>>,
+) {
+    let (weapon, inventory) = weapon_qry.single();
+    let Some(weapon) = weapon else {
+        ammo_ui_qry.single_mut().sections[0].value = String::new();
+        return;
+    };
+    let reserve = inventory.count(Item::Ammo);
+    ammo_ui_qry.single_mut().sections[0].value = match weapon.ammo() {
+        Some(_) if weapon.is_reloading() => format!("{} reloading...", weapon.def().name),
+        Some(rounds) => format!("{} {rounds} / {reserve}", weapon.def().name),
+        None => weapon.def().name.to_string(),
+    };
+}
+
+fn update_hotbar_hud(
+    inventory_qry: Query<&Inventory, With<Player>>,
+    mut hotbar_slot_ui_qry: Query<(&HotbarSlotUi, &mut BackgroundColor)>,
+    mut hotbar_item_ui_qry: Query<(&HotbarItemUi, &mut Text)>,
+) {
+    let inventory = inventory_qry.single();
+    for (hotbar_slot_ui, mut slot_color) in &mut hotbar_slot_ui_qry {
+        slot_color.0 = if hotbar_slot_ui.0 == inventory.selected() {
+            HOTBAR_SELECTED_SLOT_COLOR
+        } else {
+            HOTBAR_SLOT_COLOR
+        };
+    }
+    for (hotbar_item_ui, mut item_text) in &mut hotbar_item_ui_qry {
+        let idx = hotbar_item_ui.0;
+        item_text.sections[0].value = match inventory.slot(idx) {
+            Some(stack) if stack.count > 1 => {
+                format!("{}\n{} x{}", idx + 1, stack.item.name(), stack.count)
+            }
+            Some(stack) => format!("{}\n{}", idx + 1, stack.item.name()),
+            None => format!("{}", idx + 1),
+        };
+    }
+}
+
+fn update_boss_hud(
+    boss_qry: Query<(&Boss, &ZombieHealth)>,
+    mut boss_ui_qry: Query<&mut Style, (With<BossUi>, Without<BossHealthUi>)>,
+    mut boss_name_ui_qry: Query<&mut Text, With<BossNameUi>>,
+    mut boss_hp_ui_qry: Query<&mut Style, (With<BossHealthUi>, Without<BossUi>)>,
+) {
+    let mut boss_ui = boss_ui_qry.single_mut();
+    let Some((boss, boss_health)) = boss_qry.iter().next() else {
+        boss_ui.display = Display::None;
+        return;
+    };
+    boss_ui.display = Display::Flex;
+    boss_name_ui_qry.single_mut().sections[0].value = boss.name().to_string();
+    boss_hp_ui_qry.single_mut().width = Val::Percent(boss_health.fraction() * 100.);
+}
+
 pub fn ui_plugin(app: &mut App) {
     app.add_systems(
         OnEnter(GameState::Setup),
@@ -174,8 +417,13 @@ pub fn ui_plugin(app: &mut App) {
     )
     .add_systems(
         Update,
-        update_hud
-            .after(task::update_task_timers)
+        (
+            update_hud.after(task::update_task_timers),
+            update_stamina_hud,
+            update_ammo_hud,
+            update_hotbar_hud,
+            update_boss_hud,
+        )
             .run_if(in_state(GameState::Playing)),
     );
 }
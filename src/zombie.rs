@@ -1,15 +1,57 @@
 use {
     super::{
-        asset_owner::TextureAtlasOwner, game_state::GameState, level::LEVEL_SIZE, player::Player,
+        animation::{AnimationClip, AnimationSet, MovementClips, SpriteAnimation},
+        asset_owner::TextureAtlasOwner,
+        boss::{Boss, Charging},
+        collision::{self, ZOMBIE_GROUP},
+        combat::{Knockback, MeleeHitEvent},
+        game_state::GameState,
+        house::{HouseTarget, ZombieGoal},
+        level::LEVEL_SIZE,
+        lod::ZombieLod,
+        movement::{Movement, MovementParams},
+        perception::Perception,
+        player::Player,
+        primary_camera::PrimaryCamera,
+        shot::ShotHitEvent,
+        steering::{Steering, SteeringSet},
+        telegraph::{self, Emerging},
+        throwable::{FireDamageEvent, Stunned},
+        tile::{Tile, AVG_TILE_DIMENSION},
     },
     bevy::prelude::*,
     bevy_rapier2d::prelude::*,
-    rand::Rng,
 };
 
+pub const ZOMBIE_RADIUS: f32 = 15.;
+const ZOMBIE_REACH: f32 = AVG_TILE_DIMENSION * 0.75;
+const ZOMBIE_HOUSE_REACH: f32 = AVG_TILE_DIMENSION;
+const ZOMBIE_ATTACK_SECS: f32 = 1.5;
+pub const ZOMBIE_MOVEMENT: MovementParams = MovementParams {
+    max_speed: 15.,
+    acceleration: 60.,
+    deceleration: 60.,
+};
+const ZOMBIE_IDLE: AnimationClip = AnimationClip::looping(&[0], 1.);
+const ZOMBIE_WALK: AnimationClip = AnimationClip::looping(&[0, 1], 0.3);
+const ZOMBIE_ATTACK: AnimationClip = AnimationClip::one_shot(&[1, 0, 1], 0.1);
+const ZOMBIE_HIT: AnimationClip = AnimationClip::one_shot(&[1], 0.15);
+
 #[derive(Component)]
 pub struct Zombie;
 
+#[derive(Component)]
+pub struct AttackCooldown(Timer);
+
+#[derive(Event)]
+pub struct ZombieAttackEvent {
+    pub zombie_id: Entity,
+    pub target_id: Entity,
+}
+
+#[derive(Event)]
+pub struct ZombieKilledEvent;
+
 #[derive(Resource)]
 pub struct ZombieSpawns(pub Vec<Vec2>);
 
@@ -21,56 +63,408 @@ impl ZombieSpawns {
     }
 }
 
-fn spawn_zombie(
+#[derive(Resource)]
+pub struct ZombieWave {
+    number: u32,
+    timer: Timer,
+}
+
+impl ZombieWave {
+    const SECS: f32 = 15.;
+
+    fn new() -> Self {
+        Self {
+            number: 0,
+            timer: Timer::from_seconds(Self::SECS, TimerMode::Repeating),
+        }
+    }
+}
+
+#[derive(Event)]
+pub struct WaveStartedEvent {
+    pub wave: u32,
+}
+
+#[derive(Component)]
+pub struct ZombieHealth {
+    current: f32,
+    max: f32,
+}
+
+impl ZombieHealth {
+    pub fn new(max: f32) -> Self {
+        Self { current: max, max }
+    }
+
+    pub fn fraction(&self) -> f32 {
+        self.current / self.max
+    }
+
+    pub fn damage(&mut self, amount: f32) -> bool {
+        self.current = (self.current - amount).max(0.);
+        self.current == 0.
+    }
+}
+
+#[derive(Bundle)]
+struct ZombieStateBundle {
+    zombie: Zombie,
+    health: ZombieHealth,
+    perception: Perception,
+    steering: Steering,
+    lod: ZombieLod,
+    movement_clips: MovementClips,
+    attack_cooldown: AttackCooldown,
+    kcc: KinematicCharacterController,
+    movement: Movement,
+}
+
+impl ZombieStateBundle {
+    fn new() -> Self {
+        Self {
+            zombie: Zombie,
+            health: ZombieHealth::new(3.),
+            perception: Perception::default(),
+            steering: Steering::default(),
+            lod: ZombieLod::default(),
+            movement_clips: MovementClips {
+                idle: ZOMBIE_IDLE,
+                walk: ZOMBIE_WALK,
+            },
+            attack_cooldown: AttackCooldown(Timer::from_seconds(
+                ZOMBIE_ATTACK_SECS,
+                TimerMode::Once,
+            )),
+            kcc: KinematicCharacterController::default(),
+            movement: Movement::new(ZOMBIE_MOVEMENT),
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ZombiePool {
+    free: Vec<Entity>,
+    active: usize,
+    pending: usize,
+}
+
+impl ZombiePool {
+    //Zombies out plus spawn telegraphs holding a slot for the zombie they'll turn into
+    fn population(&self) -> usize {
+        self.active + self.pending
+    }
+
+    pub fn reserve(&mut self, zombie_population: &ZombiePopulation) -> bool {
+        if self.population() >= zombie_population.cap {
+            return false;
+        }
+        self.pending += 1;
+        true
+    }
+}
+
+#[derive(Resource)]
+pub struct ZombiePopulation {
+    pub cap: usize,
+    queued: u32,
+}
+
+impl ZombiePopulation {
+    const DEFAULT_CAP: usize = 150;
+}
+
+//Every spawn goes through the population cap, so bosses and summons can't exceed it either
+pub fn spawn_zombie(
+    cmds: &mut Commands,
+    zombie_pool: &mut ZombiePool,
+    zombie_population: &ZombiePopulation,
+    zombie_pos: Vec2,
+    zombie_tex_atlas: &Res<TextureAtlasOwner<Zombie>>,
+) -> Option<Entity> {
+    zombie_pool
+        .reserve(zombie_population)
+        .then(|| spawn_reserved_zombie(cmds, zombie_pool, zombie_pos, zombie_tex_atlas))
+}
+
+//Fills a slot taken earlier with ZombiePool::reserve
+pub fn spawn_reserved_zombie(
+    cmds: &mut Commands,
+    zombie_pool: &mut ZombiePool,
+    zombie_pos: Vec2,
+    zombie_tex_atlas: &Res<TextureAtlasOwner<Zombie>>,
+) -> Entity {
+    zombie_pool.pending -= 1;
+    zombie_pool.active += 1;
+    let zombie_xform = Transform::from_translation(zombie_pos.extend(8.));
+    if let Some(zombie_id) = zombie_pool.free.pop() {
+        cmds.entity(zombie_id).remove::<ColliderDisabled>().insert((
+            ZombieStateBundle::new(),
+            zombie_xform,
+            Sprite::default(),
+            Visibility::Inherited,
+            Collider::ball(ZOMBIE_RADIUS),
+            zombie_tex_atlas.animated(ZOMBIE_IDLE),
+        ));
+        return zombie_id;
+    }
+    cmds.spawn((
+        StateScoped(GameState::Playing),
+        ZombieStateBundle::new(),
+        Collider::ball(ZOMBIE_RADIUS),
+        collision::groups(ZOMBIE_GROUP),
+        SpriteBundle {
+            transform: zombie_xform,
+            texture: zombie_tex_atlas.texture(),
+            ..default()
+        },
+        zombie_tex_atlas.animated(ZOMBIE_IDLE),
+    ))
+    .id()
+}
+
+//Hides the zombie and strips its state so the entity can be reinitialised by the next spawn
+pub fn recycle_zombie(cmds: &mut Commands, zombie_pool: &mut ZombiePool, zombie_id: Entity) {
+    cmds.entity(zombie_id)
+        .remove::<(
+            ZombieStateBundle,
+            ZombieGoal,
+            Boss,
+            Charging,
+            Emerging,
+            Knockback,
+            Stunned,
+        )>()
+        .insert((Visibility::Hidden, ColliderDisabled));
+    zombie_pool.free.push(zombie_id);
+    zombie_pool.active -= 1;
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_zombie_wave(
     mut cmds: Commands,
+    mut zombie_wave: ResMut<ZombieWave>,
+    mut zombie_pool: ResMut<ZombiePool>,
+    mut zombie_population: ResMut<ZombiePopulation>,
+    mut wave_started_evw: EventWriter<WaveStartedEvent>,
     time: Res<Time>,
     zombie_spawns: Res<ZombieSpawns>,
-    zombie_tex_atlas: Res<TextureAtlasOwner<Zombie>>,
+    tile_tex_atlas: Res<TextureAtlasOwner<Tile>>,
+    player_qry: Query<&Transform, With<Player>>,
+    primary_cam_qry: Query<(&OrthographicProjection, &Transform), With<PrimaryCamera>>,
 ) {
-    let t = time.elapsed_seconds() as u32;
-    if t % 15 == 0 {
-        let mut thread_rng = rand::thread_rng();
-        for _ in 0..(t / 15) {
-            cmds.spawn((
-                StateScoped(GameState::Playing),
-                Zombie,
-                Collider::ball(15.),
-                SpriteBundle {
-                    transform: Transform::from_translation(
-                        zombie_spawns.0[thread_rng.gen_range(0..zombie_spawns.0.len())].extend(8.),
-                    ),
-                    texture: zombie_tex_atlas.texture(),
-                    ..default()
-                },
-                TextureAtlas {
-                    index: 0,
-                    layout: zombie_tex_atlas.layout(),
-                },
-                KinematicCharacterController::default(),
-                Velocity::linear(Vec2::splat(15.)),
-            ));
+    if !zombie_wave.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    zombie_wave.number += 1;
+    wave_started_evw.send(WaveStartedEvent {
+        wave: zombie_wave.number,
+    });
+
+    let wanted = zombie_wave.number + zombie_population.queued;
+    let player_pos = player_qry.single().translation.truncate();
+    let visible_area = telegraph::visible_area(&primary_cam_qry);
+    let mut thread_rng = rand::thread_rng();
+    for spawned in 0..wanted {
+        //Zombies that don't fit under the cap, or have nowhere to come from, join the next wave
+        let telegraph_id = telegraph::pick_spawn_point(
+            &zombie_spawns.0,
+            player_pos,
+            visible_area,
+            &mut thread_rng,
+        )
+        .and_then(|spawn_pos| {
+            telegraph::spawn_telegraph(
+                &mut cmds,
+                &mut zombie_pool,
+                &zombie_population,
+                spawn_pos,
+                &tile_tex_atlas,
+            )
+        });
+        if telegraph_id.is_none() {
+            zombie_population.queued = wanted - spawned;
+            return;
         }
     }
+    zombie_population.queued = 0;
 }
 
+#[allow(clippy::type_complexity)]
 fn zombie_movement(
-    mut zombie_qry: Query<(&mut KinematicCharacterController, &mut Transform, &Velocity), With<Zombie>>,
-    player_qry: Query<&Transform, (With<Player>, Without<Zombie>)>,
+    mut zombie_qry: Query<
+        (
+            &mut KinematicCharacterController,
+            &mut Transform,
+            &mut Movement,
+            &Steering,
+        ),
+        (
+            With<Zombie>,
+            Without<Charging>,
+            Without<Emerging>,
+            Without<Knockback>,
+            Without<Stunned>,
+        ),
+    >,
     time: Res<Time>,
 ) {
     let dt = time.delta_seconds();
-    let player_xform = player_qry.single();
-    for (mut zombie_kcc, mut zombie_xform, zombie_vel) in &mut zombie_qry {
-        let zombie_dir = -(zombie_xform.translation.truncate() - player_xform.translation.truncate()).normalize();
-        let theta = -zombie_dir.angle_between(Vec2::X);
-        zombie_xform.rotation = Quat::from_rotation_z(theta);
-        let zombie_displacement = zombie_dir * zombie_vel.linvel * dt;
-        zombie_kcc.translation = Some(zombie_displacement);
+    for (mut zombie_kcc, mut zombie_xform, mut zombie_movement, zombie_steering) in &mut zombie_qry
+    {
+        let zombie_dir = zombie_steering.heading();
+        if zombie_dir != Vec2::ZERO {
+            let theta = -zombie_dir.angle_between(Vec2::X);
+            zombie_xform.rotation = Quat::from_rotation_z(theta);
+        }
+        zombie_kcc.translation = Some(zombie_movement.step(zombie_dir, dt));
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn zombie_attack(
+    mut zombie_qry: Query<
+        (Entity, &Transform, &mut AttackCooldown, Option<&ZombieGoal>),
+        (With<Zombie>, Without<Emerging>, Without<Stunned>),
+    >,
+    player_qry: Query<(Entity, &Transform), With<Player>>,
+    house_target_qry: Query<&Transform, With<HouseTarget>>,
+    mut attack_evw: EventWriter<ZombieAttackEvent>,
+    time: Res<Time>,
+) {
+    let dt = time.delta();
+    let (player_id, player_xform) = player_qry.single();
+    let player_pos = player_xform.translation.truncate();
+    for (zombie_id, zombie_xform, mut attack_cooldown, zombie_goal) in &mut zombie_qry {
+        if !attack_cooldown.0.tick(dt).finished() {
+            continue;
+        }
+        let zombie_pos = zombie_xform.translation.truncate();
+        let target_id = if zombie_pos.distance(player_pos) <= ZOMBIE_REACH {
+            Some(player_id)
+        } else if let Some(&ZombieGoal::House(house_target_id)) = zombie_goal {
+            house_target_qry
+                .get(house_target_id)
+                .ok()
+                .filter(|house_target_xform| {
+                    zombie_pos.distance(house_target_xform.translation.truncate())
+                        <= ZOMBIE_HOUSE_REACH
+                })
+                .map(|_| house_target_id)
+        } else {
+            None
+        };
+        let Some(target_id) = target_id else {
+            continue;
+        };
+        attack_cooldown.0.reset();
+        attack_evw.send(ZombieAttackEvent {
+            zombie_id,
+            target_id,
+        });
+    }
+}
+
+fn play_zombie_attack_clips(
+    mut attack_evr: EventReader<ZombieAttackEvent>,
+    mut zombie_anim_qry: Query<&mut SpriteAnimation, With<Zombie>>,
+) {
+    for attack in attack_evr.read() {
+        if let Ok(mut zombie_anim) = zombie_anim_qry.get_mut(attack.zombie_id) {
+            zombie_anim.play(ZOMBIE_ATTACK);
+        }
+    }
+}
+
+//Returns whether the hit killed the zombie, or None when the target is not a living zombie
+fn damage_zombie(
+    cmds: &mut Commands,
+    zombie_pool: &mut ZombiePool,
+    zombie_qry: &mut Query<(&mut ZombieHealth, &mut SpriteAnimation), With<Zombie>>,
+    zombie_id: Entity,
+    damage: f32,
+) -> Option<bool> {
+    let (mut zombie_health, mut zombie_anim) = zombie_qry.get_mut(zombie_id).ok()?;
+    if zombie_health.current == 0. {
+        return None;
+    }
+    if zombie_health.damage(damage) {
+        recycle_zombie(cmds, zombie_pool, zombie_id);
+        return Some(true);
+    }
+    zombie_anim.play(ZOMBIE_HIT);
+    Some(false)
+}
+
+fn take_hit_damage(
+    mut cmds: Commands,
+    mut shot_hit_evr: EventReader<ShotHitEvent>,
+    mut melee_hit_evr: EventReader<MeleeHitEvent>,
+    mut fire_damage_evr: EventReader<FireDamageEvent>,
+    mut killed_evw: EventWriter<ZombieKilledEvent>,
+    mut zombie_qry: Query<(&mut ZombieHealth, &mut SpriteAnimation), With<Zombie>>,
+    mut zombie_pool: ResMut<ZombiePool>,
+) {
+    for shot_hit in shot_hit_evr.read() {
+        if damage_zombie(
+            &mut cmds,
+            &mut zombie_pool,
+            &mut zombie_qry,
+            shot_hit.target_id,
+            shot_hit.damage,
+        ) == Some(true)
+        {
+            killed_evw.send(ZombieKilledEvent);
+        }
+    }
+    for melee_hit in melee_hit_evr.read() {
+        match damage_zombie(
+            &mut cmds,
+            &mut zombie_pool,
+            &mut zombie_qry,
+            melee_hit.target_id,
+            melee_hit.damage,
+        ) {
+            Some(true) => {
+                killed_evw.send(ZombieKilledEvent);
+            }
+            Some(false) => {
+                cmds.entity(melee_hit.target_id)
+                    .insert(Knockback::new(melee_hit.knockback));
+            }
+            None => (),
+        }
+    }
+    for fire_damage in fire_damage_evr.read() {
+        if damage_zombie(
+            &mut cmds,
+            &mut zombie_pool,
+            &mut zombie_qry,
+            fire_damage.target_id,
+            fire_damage.damage,
+        ) == Some(true)
+        {
+            killed_evw.send(ZombieKilledEvent);
+        }
     }
 }
 
 pub fn zombie_plugin(app: &mut App) {
     app.insert_resource(ZombieSpawns::new())
+        .add_event::<ZombieAttackEvent>()
+        .add_event::<WaveStartedEvent>()
+        .add_event::<ZombieKilledEvent>()
+        .insert_resource(ZombiePopulation {
+            cap: ZombiePopulation::DEFAULT_CAP,
+            queued: 0,
+        })
+        .add_systems(
+            OnEnter(GameState::Playing),
+            |mut cmds: Commands, mut zombie_population: ResMut<ZombiePopulation>| {
+                cmds.insert_resource(ZombieWave::new());
+                cmds.insert_resource(ZombiePool::default());
+                zombie_population.queued = 0;
+            },
+        )
         .add_systems(
             OnEnter(GameState::Setup),
             |mut cmds: Commands,
@@ -88,9 +482,121 @@ pub fn zombie_plugin(app: &mut App) {
                 ))
             },
         )
-        .add_systems(Update, spawn_zombie.run_if(in_state(GameState::Playing)))
+        .add_systems(
+            Update,
+            (
+                spawn_zombie_wave,
+                zombie_attack,
+                (play_zombie_attack_clips, take_hit_damage).before(AnimationSet),
+            )
+                .run_if(in_state(GameState::Playing)),
+        )
         .add_systems(
             FixedUpdate,
-            zombie_movement.run_if(in_state(GameState::Playing)),
+            zombie_movement
+                .after(SteeringSet)
+                .run_if(in_state(GameState::Playing)),
         );
 }
+
+#[cfg(test)]
+mod tests {
+    use {super::*, bevy::ecs::system::RunSystemOnce};
+
+    fn world(cap: usize) -> World {
+        let mut world = World::new();
+        world.init_resource::<ZombiePool>();
+        world.insert_resource(ZombiePopulation { cap, queued: 0 });
+        world.insert_resource(TextureAtlasOwner::<Zombie>::new(default(), default()));
+        world
+    }
+
+    fn spawn(world: &mut World) -> Option<Entity> {
+        world.run_system_once(
+            |mut cmds: Commands,
+             mut zombie_pool: ResMut<ZombiePool>,
+             zombie_population: Res<ZombiePopulation>,
+             zombie_tex_atlas: Res<TextureAtlasOwner<Zombie>>| {
+                spawn_zombie(
+                    &mut cmds,
+                    &mut zombie_pool,
+                    &zombie_population,
+                    Vec2::ZERO,
+                    &zombie_tex_atlas,
+                )
+            },
+        )
+    }
+
+    fn recycle(world: &mut World, zombie_id: Entity) {
+        world.run_system_once_with(
+            zombie_id,
+            |In(zombie_id): In<Entity>, mut cmds: Commands, mut zombie_pool: ResMut<ZombiePool>| {
+                recycle_zombie(&mut cmds, &mut zombie_pool, zombie_id)
+            },
+        );
+    }
+
+    fn counts(world: &World) -> (usize, usize, usize) {
+        let zombie_pool = world.resource::<ZombiePool>();
+        (
+            zombie_pool.active,
+            zombie_pool.pending,
+            zombie_pool.free.len(),
+        )
+    }
+
+    #[test]
+    fn recycled_zombies_are_reused() {
+        let mut world = world(ZombiePopulation::DEFAULT_CAP);
+        let first_id = spawn(&mut world).unwrap();
+        spawn(&mut world).unwrap();
+        assert_eq!(counts(&world), (2, 0, 0));
+
+        recycle(&mut world, first_id);
+        assert_eq!(counts(&world), (1, 0, 1));
+        assert!(world.get::<ColliderDisabled>(first_id).is_some());
+
+        assert_eq!(spawn(&mut world), Some(first_id));
+        assert_eq!(counts(&world), (2, 0, 0));
+        assert!(world.get::<ColliderDisabled>(first_id).is_none());
+        assert_eq!(
+            world.get::<Visibility>(first_id),
+            Some(&Visibility::Inherited)
+        );
+    }
+
+    #[test]
+    fn spawns_stop_at_the_cap_until_a_zombie_is_recycled() {
+        let mut world = world(2);
+        let first_id = spawn(&mut world).unwrap();
+        spawn(&mut world).unwrap();
+        assert_eq!(spawn(&mut world), None);
+        assert_eq!(counts(&world), (2, 0, 0));
+
+        recycle(&mut world, first_id);
+        assert!(spawn(&mut world).is_some());
+        assert_eq!(counts(&world), (2, 0, 0));
+    }
+
+    #[test]
+    fn reserved_slots_count_toward_the_cap() {
+        let mut world = world(2);
+        let zombie_population = ZombiePopulation { cap: 2, queued: 0 };
+        let mut zombie_pool = world.resource_mut::<ZombiePool>();
+        assert!(zombie_pool.reserve(&zombie_population));
+        assert!(zombie_pool.reserve(&zombie_population));
+        assert!(!zombie_pool.reserve(&zombie_population));
+        assert_eq!(spawn(&mut world), None);
+        assert_eq!(counts(&world), (0, 2, 0));
+
+        world.run_system_once(
+            |mut cmds: Commands,
+             mut zombie_pool: ResMut<ZombiePool>,
+             zombie_tex_atlas: Res<TextureAtlasOwner<Zombie>>| {
+                spawn_reserved_zombie(&mut cmds, &mut zombie_pool, Vec2::ZERO, &zombie_tex_atlas)
+            },
+        );
+        assert_eq!(counts(&world), (1, 1, 0));
+    }
+}